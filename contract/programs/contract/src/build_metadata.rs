@@ -1,49 +1,228 @@
 use anchor_lang::{ prelude::{ Context, Pubkey }, solana_program::instruction::Instruction, Key };
 use mpl_token_metadata::{
-    instructions::{ CreateV1Builder },
-    types::{ Creator, PrintSupply, TokenStandard },
+    instructions::{
+        BurnV1Builder,
+        CreateV1Builder,
+        FreezeDelegatedAccountBuilder,
+        ThawDelegatedAccountBuilder,
+        UpdateV1Builder,
+        VerifyCollectionV1Builder,
+        VerifyCreatorV1Builder,
+    },
+    types::{ Collection, CollectionDetails, Data, PrintSupply, TokenStandard },
 };
 
-use crate::BookSlot;
+use crate::held_ticket::HeldTicket;
+use crate::new_ticket::NewTicket;
+use crate::{
+    nft_metadata,
+    CreateExperience,
+    Experience,
+    VerifyTicketCreator,
+};
 
-pub fn build_metadata_ix(
-    ctx: &Context<BookSlot>,
-    metadata_uri: String,
-    symbol: String
+// name, symbol, uri and creators shared by every booking NFT of an experience
+pub fn ticket_data(experience: &Experience, mint_authority: Pubkey, metadata_uri: String) -> Data {
+    let royalties = &experience.ticket_royalties;
+    let creators = royalties.creators(experience.organiser, mint_authority);
+
+    Data {
+        name: nft_metadata::ticket_name(experience),
+        symbol: experience.title.chars().take(10).collect::<String>(),
+        uri: metadata_uri,
+        seller_fee_basis_points: royalties.seller_fee_basis_points,
+        creators: Some(creators),
+    }
+}
+
+// collection NFT grouping the booking NFTs of an experience
+pub fn collection_data(experience: &Experience, mint_authority: Pubkey, metadata_uri: String) -> Data {
+    Data {
+        name: nft_metadata::collection_name(experience),
+        ..ticket_data(experience, mint_authority, metadata_uri)
+    }
+}
+
+pub fn build_collection_metadata_ix(
+    ctx: &Context<CreateExperience>,
+    collection_mint: Pubkey,
+    metadata: Pubkey,
+    master_edition: Pubkey,
+    mint_authority: Pubkey,
+    metadata_uri: String
 ) -> Instruction {
-    let (master_edition, _bump) = Pubkey::find_program_address(
-        &[
-            b"metadata",
-            mpl_token_metadata::ID.as_ref(),
-            ctx.accounts.mint.key().as_ref(),
-            b"edition",
-        ],
-        &mpl_token_metadata::ID
-    );
-
-    let creators = vec![Creator {
-        address: ctx.accounts.user.key(),
-        verified: true,
-        share: 100,
-    }];
+    let data = collection_data(&ctx.accounts.experience, mint_authority, metadata_uri);
 
     CreateV1Builder::new()
-        .metadata(ctx.accounts.metadata.key().into())
-        .mint(ctx.accounts.mint.key(), true)
-        .payer(ctx.accounts.user.key())
-        .update_authority(ctx.accounts.user.key(), true)
-        .authority(ctx.accounts.user.key())
+        .metadata(metadata)
+        .mint(collection_mint, false)
+        .payer(ctx.accounts.organiser.key())
+        .update_authority(mint_authority, true)
+        .authority(mint_authority)
         .system_program(ctx.accounts.system_program.key())
-        .spl_token_program(Some(ctx.accounts.token_program.key()))
+        .spl_token_program(Some(anchor_spl::token::ID))
         .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
-        .name(format!("{} Booking", ctx.accounts.experience.title))
-        .symbol(symbol)
-        .uri(metadata_uri)
-        .seller_fee_basis_points(0)
-        .creators(creators)
+        .name(data.name)
+        .symbol(data.symbol)
+        .uri(data.uri)
+        .seller_fee_basis_points(data.seller_fee_basis_points)
+        .creators(data.creators.unwrap_or_default())
         .is_mutable(true)
         .token_standard(TokenStandard::NonFungible)
+        .collection_details(CollectionDetails::V1 { size: 0 })
         .print_supply(PrintSupply::Zero)
-        .master_edition(Some(ctx.accounts.master_edition.key()))
+        .master_edition(Some(master_edition))
+        .instruction()
+}
+
+pub fn build_metadata_ix(
+    ticket: &NewTicket,
+    mint: Pubkey,
+    token_program: Pubkey,
+    metadata: Pubkey,
+    master_edition: Pubkey,
+    metadata_uri: String
+) -> Instruction {
+    let data = ticket_data(ticket.experience, ticket.mint_authority.key(), metadata_uri);
+
+    let mut builder = CreateV1Builder::new();
+    if let Some(collection_mint) = ticket.experience.collection_mint {
+        // added unverified here, the program authority PDA verifies it right after
+        builder.collection(Collection { verified: false, key: collection_mint });
+    }
+
+    builder
+        .metadata(metadata)
+        .mint(mint, true)
+        .payer(ticket.payer.key())
+        .update_authority(ticket.mint_authority.key(), true)
+        .authority(ticket.mint_authority.key())
+        .system_program(ticket.system_program.key())
+        .spl_token_program(Some(token_program))
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
+        .name(data.name)
+        .symbol(data.symbol)
+        .uri(data.uri)
+        .seller_fee_basis_points(data.seller_fee_basis_points)
+        .creators(data.creators.unwrap_or_default())
+        .is_mutable(true)
+        .token_standard(TokenStandard::NonFungible)
+        .print_supply(PrintSupply::Zero)
+        .master_edition(Some(master_edition))
+        .instruction()
+}
+
+pub fn build_verify_collection_ix(
+    ticket: &NewTicket,
+    metadata: Pubkey,
+    collection_mint: Pubkey,
+    collection_metadata: Pubkey,
+    collection_master_edition: Pubkey
+) -> Instruction {
+    VerifyCollectionV1Builder::new()
+        .authority(ticket.mint_authority.key())
+        .metadata(metadata)
+        .collection_mint(collection_mint)
+        .collection_metadata(Some(collection_metadata))
+        .collection_master_edition(Some(collection_master_edition))
+        .system_program(ticket.system_program.key())
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
+        .instruction()
+}
+
+pub fn build_freeze_ix(
+    ticket: &NewTicket,
+    mint: Pubkey,
+    token: Pubkey,
+    token_program: Pubkey,
+    master_edition: Pubkey
+) -> Instruction {
+    FreezeDelegatedAccountBuilder::new()
+        .delegate(ticket.mint_authority.key())
+        .token_account(token)
+        .edition(master_edition)
+        .mint(mint)
+        .token_program(token_program)
+        .instruction()
+}
+
+pub fn build_thaw_ix(
+    ticket: &HeldTicket,
+    token: Pubkey,
+    master_edition: Pubkey,
+    token_program: Pubkey
+) -> Instruction {
+    ThawDelegatedAccountBuilder::new()
+        .delegate(ticket.mint_authority.key())
+        .token_account(token)
+        .edition(master_edition)
+        .mint(ticket.mint.key())
+        .token_program(token_program)
+        .instruction()
+}
+
+pub fn build_burn_ix(
+    ticket: &HeldTicket,
+    token: Pubkey,
+    metadata: Pubkey,
+    master_edition: Pubkey,
+    collection_metadata: Option<Pubkey>,
+    token_program: Pubkey
+) -> Instruction {
+    BurnV1Builder::new()
+        .authority(ticket.holder.key())
+        .metadata(metadata)
+        .edition(Some(master_edition))
+        .collection_metadata(collection_metadata)
+        .mint(ticket.mint.key())
+        .token(token)
+        .system_program(ticket.system_program.key())
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
+        .spl_token_program(token_program)
+        .amount(1)
+        .instruction()
+}
+
+// `verified_creators` have signed the current metadata, Metaplex won't let the program unverify them
+pub fn build_update_metadata_ix(
+    ticket: &HeldTicket,
+    metadata: Pubkey,
+    metadata_uri: String,
+    verified_creators: &[Pubkey]
+) -> Instruction {
+    let mut data = ticket_data(ticket.experience, ticket.mint_authority.key(), metadata_uri);
+    for creator in data.creators.iter_mut().flatten() {
+        creator.verified |= verified_creators.contains(&creator.address);
+    }
+
+    UpdateV1Builder::new()
+        .authority(ticket.mint_authority.key())
+        .mint(ticket.mint.key())
+        .metadata(metadata)
+        .payer(ticket.payer.key())
+        .system_program(ticket.system_program.key())
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
+        .data(data)
+        .instruction()
+}
+
+pub fn build_void_metadata_ix(ticket: &HeldTicket, metadata: Pubkey, data: Data) -> Instruction {
+    UpdateV1Builder::new()
+        .authority(ticket.mint_authority.key())
+        .mint(ticket.mint.key())
+        .metadata(metadata)
+        .payer(ticket.payer.key())
+        .system_program(ticket.system_program.key())
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
+        .data(data)
+        .instruction()
+}
+
+pub fn build_verify_creator_ix(ctx: &Context<VerifyTicketCreator>) -> Instruction {
+    VerifyCreatorV1Builder::new()
+        .authority(ctx.accounts.creator.key())
+        .metadata(ctx.accounts.metadata.key())
+        .system_program(ctx.accounts.system_program.key())
+        .sysvar_instructions(anchor_lang::solana_program::sysvar::instructions::ID)
         .instruction()
 }
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+// a refund tier applied when a booking is cancelled at least `min_notice_seconds` before the slot starts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CancellationTier {
+    pub min_notice_seconds: i64,
+    pub refund_percent: u8,
+}
+
+impl CancellationTier {
+    pub const LEN: usize =
+        8 + // min_notice_seconds (i64)
+        1; // refund_percent (u8)
+}
+
+// refund tiers ordered from the longest to the shortest notice period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CancellationPolicy {
+    pub tiers: Vec<CancellationTier>,
+}
+
+impl CancellationPolicy {
+    pub const MAX_TIERS: usize = 4;
+    pub const MAX_REFUND_PERCENT: u8 = 100;
+    pub const LEN: usize = 4 + Self::MAX_TIERS * CancellationTier::LEN;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(!self.tiers.is_empty(), ErrorCode::InvalidCancellationPolicy);
+        require!(self.tiers.len() <= Self::MAX_TIERS, ErrorCode::InvalidCancellationPolicy);
+
+        for tier in &self.tiers {
+            require!(tier.min_notice_seconds >= 0, ErrorCode::InvalidCancellationPolicy);
+            require!(
+                tier.refund_percent <= Self::MAX_REFUND_PERCENT,
+                ErrorCode::InvalidCancellationPolicy
+            );
+        }
+
+        // longer notice must never earn a smaller refund
+        for pair in self.tiers.windows(2) {
+            require!(
+                pair[0].min_notice_seconds > pair[1].min_notice_seconds,
+                ErrorCode::InvalidCancellationPolicy
+            );
+            require!(
+                pair[0].refund_percent >= pair[1].refund_percent,
+                ErrorCode::InvalidCancellationPolicy
+            );
+        }
+
+        Ok(())
+    }
+
+    // refund percent for a cancellation made `notice_seconds` before the slot starts
+    pub fn refund_percent(&self, notice_seconds: i64) -> Option<u8> {
+        self.tiers
+            .iter()
+            .find(|tier| notice_seconds >= tier.min_notice_seconds)
+            .map(|tier| tier.refund_percent)
+    }
+}
+
+impl Default for CancellationPolicy {
+    // 90% refund with at least 24 hours notice
+    fn default() -> Self {
+        Self {
+            tiers: vec![CancellationTier {
+                min_notice_seconds: 24 * 60 * 60,
+                refund_percent: 90,
+            }],
+        }
+    }
+}
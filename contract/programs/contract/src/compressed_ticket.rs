@@ -0,0 +1,461 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{ AccountMeta, Instruction };
+use anchor_lang::solana_program::program::{ invoke, invoke_signed };
+use anchor_lang::solana_program::pubkey;
+use solana_keccak_hasher::hashv;
+
+use crate::build_metadata::ticket_data;
+use crate::new_ticket::NewTicket;
+use crate::{
+    nft_metadata,
+    CancelReservation,
+    CheckIn,
+    CreateTicketTree,
+    ErrorCode,
+    Experience,
+    OrganiserCancelSlot,
+    Reservation,
+};
+
+pub const BUBBLEGUM_PROGRAM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey = pubkey!(
+    "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+);
+pub const NOOP_PROGRAM_ID: Pubkey = pubkey!("noopb9bkMVfRPU8ShSpASzLDAH2WxsUyyfGeJM8uozU");
+
+// Bubblegum and account compression instruction discriminators
+const CREATE_TREE_CONFIG: [u8; 8] = [165, 83, 136, 142, 89, 202, 47, 220];
+const MINT_V1: [u8; 8] = [145, 98, 192, 118, 184, 147, 118, 104];
+const BURN: [u8; 8] = [116, 110, 29, 56, 107, 219, 42, 93];
+const VERIFY_LEAF: [u8; 8] = [124, 220, 22, 223, 104, 10, 250, 224];
+
+// offset of `num_minted` in Bubblegum's TreeConfig account
+const NUM_MINTED_OFFSET: usize = 8 + 32 + 32 + 8;
+
+// Bubblegum MetadataArgs, field order must match the Bubblegum layout
+#[derive(AnchorSerialize)]
+struct MetadataArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    seller_fee_basis_points: u16,
+    primary_sale_happened: bool,
+    is_mutable: bool,
+    edition_nonce: Option<u8>,
+    token_standard: Option<TokenStandard>,
+    collection: Option<Collection>,
+    uses: Option<Uses>,
+    token_program_version: TokenProgramVersion,
+    creators: Vec<Creator>,
+}
+
+#[derive(AnchorSerialize)]
+enum TokenStandard {
+    NonFungible,
+}
+
+#[derive(AnchorSerialize)]
+struct Collection {
+    verified: bool,
+    key: Pubkey,
+}
+
+#[derive(AnchorSerialize)]
+struct Uses {
+    use_method: u8,
+    remaining: u64,
+    total: u64,
+}
+
+#[derive(AnchorSerialize)]
+enum TokenProgramVersion {
+    Original,
+}
+
+#[derive(AnchorSerialize)]
+struct Creator {
+    address: Pubkey,
+    verified: bool,
+    share: u8,
+}
+
+// what the program keeps on the reservation to find the ticket leaf again
+pub struct CompressedTicket {
+    pub asset_id: Pubkey,
+    pub nonce: u64,
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+}
+
+// asset id Bubblegum derives for the leaf minted with `nonce`
+fn asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()],
+        &BUBBLEGUM_PROGRAM_ID
+    ).0
+}
+
+// leaf hash of a V1 Bubblegum leaf, the delegate is the owner unless the holder has set one
+fn leaf_hash(
+    asset_id: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    nonce: u64,
+    data_hash: &[u8; 32],
+    creator_hash: &[u8; 32]
+) -> [u8; 32] {
+    hashv(
+        &[
+            &[1],
+            asset_id.as_ref(),
+            owner.as_ref(),
+            delegate.as_ref(),
+            &nonce.to_le_bytes(),
+            data_hash,
+            creator_hash,
+        ]
+    ).to_bytes()
+}
+
+fn creator_hash(creators: &[Creator]) -> [u8; 32] {
+    let creator_data = creators
+        .iter()
+        .map(|c| [c.address.as_ref(), &[c.verified as u8], &[c.share]].concat())
+        .collect::<Vec<_>>();
+    hashv(
+        &creator_data
+            .iter()
+            .map(|c| c.as_slice())
+            .collect::<Vec<_>>()
+    ).to_bytes()
+}
+
+fn data_hash(metadata: &MetadataArgs) -> Result<[u8; 32]> {
+    let metadata_hash = hashv(&[&metadata.try_to_vec()?]);
+    Ok(hashv(&[&metadata_hash.to_bytes(), &metadata.seller_fee_basis_points.to_le_bytes()]).to_bytes())
+}
+
+fn ix_data<T: AnchorSerialize>(discriminator: [u8; 8], args: &T) -> Result<Vec<u8>> {
+    let mut data = discriminator.to_vec();
+    args.serialize(&mut data)?;
+    Ok(data)
+}
+
+fn proof_metas(proof: &[AccountInfo]) -> Vec<AccountMeta> {
+    proof
+        .iter()
+        .map(|node| AccountMeta::new_readonly(node.key(), false))
+        .collect()
+}
+
+// registers the organiser's merkle tree with Bubblegum, the program authority PDA becomes its creator
+pub fn create_tree(ctx: &Context<CreateTicketTree>, max_depth: u32, max_buffer_size: u32) -> Result<()> {
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(ctx.accounts.tree_config.key(), false),
+            AccountMeta::new(ctx.accounts.merkle_tree.key(), false),
+            AccountMeta::new(ctx.accounts.organiser.key(), true),
+            AccountMeta::new_readonly(ctx.accounts.mint_authority.key(), true),
+            AccountMeta::new_readonly(NOOP_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ctx.accounts.system_program.key(), false)
+        ],
+        data: ix_data(CREATE_TREE_CONFIG, &(max_depth, max_buffer_size, Some(false)))?,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            ctx.accounts.bubblegum_program.to_account_info(),
+            ctx.accounts.tree_config.to_account_info(),
+            ctx.accounts.merkle_tree.to_account_info(),
+            ctx.accounts.organiser.to_account_info(),
+            ctx.accounts.mint_authority.to_account_info(),
+            ctx.accounts.log_wrapper.to_account_info(),
+            ctx.accounts.compression_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[&[b"mint_authority", &[ctx.bumps.mint_authority]]]
+    )?;
+
+    Ok(())
+}
+
+// mints the booking ticket as a compressed NFT leaf owned by the ticket's owner
+pub fn mint_ticket(ticket: &NewTicket) -> Result<CompressedTicket> {
+    let (
+        Some(merkle_tree),
+        Some(tree_config),
+        Some(bubblegum_program),
+        Some(log_wrapper),
+        Some(compression_program),
+    ) = (
+        &ticket.merkle_tree,
+        &ticket.tree_config,
+        &ticket.bubblegum_program,
+        &ticket.log_wrapper,
+        &ticket.compression_program,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    require!(
+        ticket.experience.merkle_tree == Some(merkle_tree.key()),
+        ErrorCode::InvalidTicketTree
+    );
+
+    // the next leaf takes the tree's mint count as its nonce
+    let nonce = {
+        let data = tree_config.try_borrow_data()?;
+        let bytes = data
+            .get(NUM_MINTED_OFFSET..NUM_MINTED_OFFSET + 8)
+            .ok_or(ErrorCode::InvalidTicketTree)?;
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    };
+
+    let asset_id = asset_id(&merkle_tree.key(), nonce);
+
+    let metadata_uri = nft_metadata::create_metadata_uri(
+        ticket.experience,
+        &ticket.reservation,
+        &asset_id,
+        ticket.slot.start_time,
+        ticket.slot.end_time,
+        ticket.seats
+    )?;
+    let data = ticket_data(ticket.experience, ticket.mint_authority.key(), metadata_uri);
+    let metadata = MetadataArgs {
+        name: data.name,
+        symbol: data.symbol,
+        uri: data.uri,
+        seller_fee_basis_points: data.seller_fee_basis_points,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: data.creators
+            .unwrap_or_default()
+            .into_iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect(),
+    };
+    let minted = CompressedTicket {
+        asset_id,
+        nonce,
+        data_hash: data_hash(&metadata)?,
+        creator_hash: creator_hash(&metadata.creators),
+    };
+
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(tree_config.key(), false),
+            AccountMeta::new_readonly(ticket.owner.key(), false),
+            AccountMeta::new_readonly(ticket.owner.key(), false),
+            AccountMeta::new(merkle_tree.key(), false),
+            AccountMeta::new_readonly(ticket.payer.key(), true),
+            AccountMeta::new_readonly(ticket.mint_authority.key(), true),
+            AccountMeta::new_readonly(log_wrapper.key(), false),
+            AccountMeta::new_readonly(compression_program.key(), false),
+            AccountMeta::new_readonly(ticket.system_program.key(), false)
+        ],
+        data: ix_data(MINT_V1, &metadata)?,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            bubblegum_program.clone(),
+            tree_config.clone(),
+            ticket.owner.clone(),
+            merkle_tree.clone(),
+            ticket.payer.clone(),
+            ticket.mint_authority.clone(),
+            log_wrapper.clone(),
+            compression_program.clone(),
+            ticket.system_program.clone(),
+        ],
+        &[&[b"mint_authority", &[ticket.mint_authority_bump]]]
+    )?;
+
+    Ok(minted)
+}
+
+// burns the ticket leaf, the proof nodes are passed as remaining accounts
+pub fn burn_ticket<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CancelReservation<'info>>,
+    root: Option<[u8; 32]>
+) -> Result<()> {
+    let (
+        Some(root),
+        Some(merkle_tree),
+        Some(tree_config),
+        Some(bubblegum_program),
+        Some(log_wrapper),
+        Some(compression_program),
+    ) = (
+        root,
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.tree_config,
+        &ctx.accounts.bubblegum_program,
+        &ctx.accounts.log_wrapper,
+        &ctx.accounts.compression_program,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    require!(
+        ctx.accounts.experience.merkle_tree == Some(merkle_tree.key()),
+        ErrorCode::InvalidTicketTree
+    );
+
+    let reservation = &ctx.accounts.reservation;
+    let leaf_delegate = ctx.accounts.leaf_delegate
+        .as_ref()
+        .map_or_else(|| ctx.accounts.user.to_account_info(), |delegate| delegate.to_account_info());
+    let mut accounts = vec![
+        AccountMeta::new_readonly(tree_config.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.user.key(), true),
+        AccountMeta::new_readonly(leaf_delegate.key(), false),
+        AccountMeta::new(merkle_tree.key(), false),
+        AccountMeta::new_readonly(log_wrapper.key(), false),
+        AccountMeta::new_readonly(compression_program.key(), false),
+        AccountMeta::new_readonly(ctx.accounts.system_program.key(), false)
+    ];
+    accounts.extend(proof_metas(ctx.remaining_accounts));
+    let ix = Instruction {
+        program_id: BUBBLEGUM_PROGRAM_ID,
+        accounts,
+        data: ix_data(
+            BURN,
+            &(
+                root,
+                reservation.ticket_data_hash,
+                reservation.ticket_creator_hash,
+                reservation.ticket_nonce,
+                reservation.ticket_nonce as u32,
+            )
+        )?,
+    };
+
+    let mut infos = vec![
+        bubblegum_program.to_account_info(),
+        tree_config.to_account_info(),
+        ctx.accounts.user.to_account_info(),
+        leaf_delegate,
+        merkle_tree.to_account_info(),
+        log_wrapper.to_account_info(),
+        compression_program.to_account_info(),
+        ctx.accounts.system_program.to_account_info()
+    ];
+    infos.extend(ctx.remaining_accounts.iter().cloned());
+    invoke(&ix, &infos)?;
+
+    Ok(())
+}
+
+// proves `owner` holds the reservation's ticket leaf with `delegate` under `root`, the proof nodes are passed as remaining accounts
+#[allow(clippy::too_many_arguments)]
+fn verify_leaf<'info>(
+    experience: &Experience,
+    reservation: &Reservation,
+    merkle_tree: Option<&UncheckedAccount<'info>>,
+    compression_program: Option<&UncheckedAccount<'info>>,
+    proof: &[AccountInfo<'info>],
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    root: Option<[u8; 32]>
+) -> Result<()> {
+    let (Some(root), Some(merkle_tree), Some(compression_program)) = (
+        root,
+        merkle_tree,
+        compression_program,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    require!(experience.merkle_tree == Some(merkle_tree.key()), ErrorCode::InvalidTicketTree);
+
+    let leaf = leaf_hash(
+        &reservation.nft_mint,
+        owner,
+        delegate,
+        reservation.ticket_nonce,
+        &reservation.ticket_data_hash,
+        &reservation.ticket_creator_hash
+    );
+
+    let mut accounts = vec![AccountMeta::new_readonly(merkle_tree.key(), false)];
+    accounts.extend(proof_metas(proof));
+    let ix = Instruction {
+        program_id: ACCOUNT_COMPRESSION_PROGRAM_ID,
+        accounts,
+        data: ix_data(VERIFY_LEAF, &(root, leaf, reservation.ticket_nonce as u32))?,
+    };
+
+    let mut infos = vec![compression_program.to_account_info(), merkle_tree.to_account_info()];
+    infos.extend(proof.iter().cloned());
+    invoke(&ix, &infos)?;
+
+    Ok(())
+}
+
+// proves the canceller holds the ticket leaf before anything is refunded
+pub fn verify_holder<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CancelReservation<'info>>,
+    root: Option<[u8; 32]>
+) -> Result<()> {
+    verify_leaf(
+        &ctx.accounts.experience,
+        &ctx.accounts.reservation,
+        ctx.accounts.merkle_tree.as_ref(),
+        ctx.accounts.compression_program.as_ref(),
+        ctx.remaining_accounts,
+        &ctx.accounts.user.key(),
+        &ctx.accounts.leaf_delegate.as_ref().map_or(ctx.accounts.user.key(), |delegate| delegate.key()),
+        root
+    )
+}
+
+// proves the booker the organiser refunds holds the ticket leaf, `proof` is the head of remaining accounts
+pub fn verify_booker<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, OrganiserCancelSlot<'info>>,
+    proof: &[AccountInfo<'info>],
+    root: Option<[u8; 32]>
+) -> Result<()> {
+    let booker = ctx.accounts.booker.key();
+    verify_leaf(
+        &ctx.accounts.experience,
+        &ctx.accounts.reservation,
+        ctx.accounts.merkle_tree.as_ref(),
+        ctx.accounts.compression_program.as_ref(),
+        proof,
+        &booker,
+        &ctx.accounts.leaf_delegate.as_ref().map_or(booker, |delegate| delegate.key()),
+        root
+    )
+}
+
+// proves the guest signing at the door holds the ticket leaf, the proof nodes are passed as remaining accounts
+pub fn verify_ticket<'info>(
+    ctx: &Context<'_, '_, '_, 'info, CheckIn<'info>>,
+    root: Option<[u8; 32]>
+) -> Result<()> {
+    let Some(holder) = &ctx.accounts.holder else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    verify_leaf(
+        &ctx.accounts.experience,
+        &ctx.accounts.reservation,
+        ctx.accounts.merkle_tree.as_ref(),
+        ctx.accounts.compression_program.as_ref(),
+        ctx.remaining_accounts,
+        &holder.key(),
+        &ctx.accounts.leaf_delegate.as_ref().map_or(holder.key(), |delegate| delegate.key()),
+        root
+    )
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+
+use crate::{ CancelReservation, Experience, OrganiserCancelSlot, UpdateReservation };
+
+// the booking NFT of a reservation that is being cancelled or changed, gathered from whichever
+// instruction touches it so they all burn, rewrite and void tickets through the same helpers
+pub struct HeldTicket<'a, 'info> {
+    pub experience: &'a Account<'info, Experience>,
+    // owner of the ticket, signs the burn and gets the ticket's rent back
+    pub holder: AccountInfo<'info>,
+    // funds the metadata growing when the ticket is rewritten or voided
+    pub payer: AccountInfo<'info>,
+    pub holder_nft_account: Option<AccountInfo<'info>>,
+    pub mint: AccountInfo<'info>,
+    pub mint_authority: AccountInfo<'info>,
+    pub mint_authority_bump: u8,
+    pub metadata: Option<AccountInfo<'info>>,
+    pub master_edition: Option<AccountInfo<'info>>,
+    pub collection_metadata: Option<AccountInfo<'info>>,
+    pub token_program: Option<AccountInfo<'info>>,
+    pub ticket_token_program: Option<AccountInfo<'info>>,
+    pub system_program: AccountInfo<'info>,
+    pub metadata_program: Option<AccountInfo<'info>>,
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+}
+
+impl<'a, 'info> HeldTicket<'a, 'info> {
+    pub fn cancelled(ctx: &'a Context<'_, '_, '_, 'info, CancelReservation<'info>>) -> Self {
+        let accounts = &ctx.accounts;
+        Self {
+            experience: &accounts.experience,
+            holder: accounts.user.to_account_info(),
+            payer: accounts.user.to_account_info(),
+            holder_nft_account: accounts.user_nft_account.as_ref().map(|account| account.to_account_info()),
+            mint: accounts.mint.to_account_info(),
+            mint_authority: accounts.mint_authority.to_account_info(),
+            mint_authority_bump: ctx.bumps.mint_authority,
+            metadata: accounts.metadata.as_ref().map(|account| account.to_account_info()),
+            master_edition: accounts.master_edition.as_ref().map(|account| account.to_account_info()),
+            collection_metadata: accounts.collection_metadata.as_ref().map(|account| account.to_account_info()),
+            token_program: Some(accounts.token_program.to_account_info()),
+            ticket_token_program: accounts.ticket_token_program.as_ref().map(|program| program.to_account_info()),
+            system_program: accounts.system_program.to_account_info(),
+            metadata_program: accounts.metadata_program.as_ref().map(|program| program.to_account_info()),
+            sysvar_instructions: accounts.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+        }
+    }
+
+    pub fn rescheduled(ctx: &'a Context<'_, '_, '_, 'info, UpdateReservation<'info>>) -> Self {
+        let accounts = &ctx.accounts;
+        Self {
+            experience: &accounts.experience,
+            holder: accounts.user.to_account_info(),
+            payer: accounts.user.to_account_info(),
+            holder_nft_account: accounts.user_nft_account.as_ref().map(|account| account.to_account_info()),
+            mint: accounts.mint.to_account_info(),
+            mint_authority: accounts.mint_authority.to_account_info(),
+            mint_authority_bump: ctx.bumps.mint_authority,
+            metadata: accounts.metadata.as_ref().map(|account| account.to_account_info()),
+            master_edition: None,
+            collection_metadata: None,
+            token_program: accounts.token_program.as_ref().map(|program| program.to_account_info()),
+            ticket_token_program: accounts.ticket_token_program.as_ref().map(|program| program.to_account_info()),
+            system_program: accounts.system_program.to_account_info(),
+            metadata_program: accounts.metadata_program.as_ref().map(|program| program.to_account_info()),
+            sysvar_instructions: accounts.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+        }
+    }
+
+    pub fn organiser_cancelled(ctx: &'a Context<'_, '_, '_, 'info, OrganiserCancelSlot<'info>>) -> Self {
+        let accounts = &ctx.accounts;
+        Self {
+            experience: &accounts.experience,
+            holder: accounts.booker.to_account_info(),
+            payer: accounts.organiser.to_account_info(),
+            holder_nft_account: accounts.booker_nft_account.as_ref().map(|account| account.to_account_info()),
+            mint: accounts.mint.to_account_info(),
+            mint_authority: accounts.mint_authority.to_account_info(),
+            mint_authority_bump: ctx.bumps.mint_authority,
+            metadata: accounts.metadata.as_ref().map(|account| account.to_account_info()),
+            master_edition: accounts.master_edition.as_ref().map(|account| account.to_account_info()),
+            collection_metadata: accounts.collection_metadata.as_ref().map(|account| account.to_account_info()),
+            token_program: accounts.token_program.as_ref().map(|program| program.to_account_info()),
+            ticket_token_program: accounts.ticket_token_program.as_ref().map(|program| program.to_account_info()),
+            system_program: accounts.system_program.to_account_info(),
+            metadata_program: accounts.metadata_program.as_ref().map(|program| program.to_account_info()),
+            sysvar_instructions: accounts.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+        }
+    }
+}
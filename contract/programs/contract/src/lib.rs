@@ -1,172 +1,218 @@
-mod nft_metadata;
+pub mod nft_metadata;
+pub mod cancellation_policy;
 use anchor_lang::prelude::*;
-use anchor_spl::{ associated_token::AssociatedToken, token::{ Token, spl_token } };
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{ Mint, Token, TokenAccount },
+    token_2022::Token2022,
+    token_interface,
+};
 mod build_metadata;
+mod compressed_ticket;
+mod held_ticket;
+mod metaplex_ticket;
+mod new_ticket;
+pub mod recurring_slots;
+pub mod resale;
+mod slot_registry;
+pub mod ticket_royalties;
+mod token_2022_ticket;
+pub mod vault;
+mod waitlist;
+
+use cancellation_policy::CancellationPolicy;
+use held_ticket::HeldTicket;
+use new_ticket::NewTicket;
+use recurring_slots::SlotInterval;
+use ticket_royalties::TicketRoyalties;
 
 declare_id!("CsFsWk5NwBuo7bGbryvyujzrtMnz6458EphQ5xytMMpM");
 
 #[program]
 pub mod contract {
-    use anchor_lang::solana_program::program::{ invoke, invoke_signed };
-    use anchor_lang::solana_program::system_instruction::{ self };
-    use anchor_spl::token::{ mint_to, MintTo };
-
-    use crate::build_metadata::build_metadata_ix;
-
     use super::*;
 
-    pub fn book_slot(ctx: Context<BookSlot>, start_time: i64) -> Result<()> {
+    pub fn book_slot<'info>(
+        ctx: Context<'_, '_, '_, 'info, BookSlot<'info>>,
+        start_time: i64,
+        seats: u16
+    ) -> Result<()> {
         let experience_key = ctx.accounts.experience.key();
         let slot = &mut ctx.accounts.slot;
-        let slot_price = slot.price;
 
-        require!(!slot.is_booked, ErrorCode::AlreadyBooked);
-        require!(**ctx.accounts.user.lamports.borrow() >= slot_price, ErrorCode::InsufficientFunds);
-
-        // Transfer payment to organiser
-        invoke(
-            &system_instruction::transfer(
-                &ctx.accounts.user.key(),
-                &ctx.accounts.organiser.key(),
-                slot_price
-            ),
-            &[
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.organiser.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
+        // one reservation and ticket covers the whole party
+        require!(seats > 0, ErrorCode::InvalidSeatCount);
+
+        // a waitlisted user claims the seats held for their offer, minus anything they prepaid
+        let mut prepaid = 0;
+        if let Some(entry) = &ctx.accounts.waitlist_entry {
+            let current_time = Clock::get()?.unix_timestamp;
+            require!(
+                entry.position == slot.waitlist_head && entry.seats == seats && entry.has_live_offer(current_time),
+                ErrorCode::NoWaitlistOffer
+            );
+            slot.held_seats = slot.held_seats.checked_sub(seats).ok_or(ErrorCode::NoWaitlistOffer)?;
+            slot.pop_waitlist();
+            prepaid = entry.deposit;
+        }
+
+        slot.book_seats(seats)?;
+        let amount = slot.price.checked_mul(seats as u64).ok_or(ErrorCode::AmountOverflow)?;
+
+        // Hold the payment in the experience vault until the slot has ended
+        let escrow = vault::TokenEscrow::load(
+            &ctx.accounts.experience,
+            &ctx.accounts.vault,
+            Some(&ctx.accounts.token_program),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        vault::deposit(
+            escrow.as_ref(),
+            &ctx.accounts.user,
+            ctx.accounts.user_payment_account.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.system_program,
+            amount.checked_sub(prepaid).ok_or(ErrorCode::AmountOverflow)?
         )?;
-
-        slot.is_booked = true;
-        slot.booker = Some(ctx.accounts.user.key());
 
         // Set reservation fields
         let reservation = &mut ctx.accounts.reservation;
         reservation.experience_id = experience_key;
         reservation.user = ctx.accounts.user.key();
+        reservation.booker = ctx.accounts.user.key();
         reservation.time_slot = start_time;
         reservation.nft_mint = ctx.accounts.mint.key();
         reservation.start_time = slot.start_time;
         reservation.end_time = slot.end_time;
         reservation.is_active = true;
-
-        // Mint account creation
-        let mint_rent = Rent::get()?.minimum_balance(82);
-        invoke(
-            &system_instruction::create_account(
-                &ctx.accounts.user.key(),
-                &ctx.accounts.mint.key(),
-                mint_rent,
-                82,
-                &ctx.accounts.token_program.key()
-            ),
-            &[
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
-        )?;
-
-        // Initialize mint
-        let mint_ix = spl_token::instruction::initialize_mint(
-            &ctx.accounts.token_program.key(),
-            &ctx.accounts.mint.key(),
-            &ctx.accounts.user.key(),
-            Some(&ctx.accounts.user.key()),
-            0
-        )?;
-        invoke(
-            &mint_ix,
-            &[
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-            ]
-        )?;
-
-        // Create ATA (associated token account)
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.associated_token_program.to_account_info(),
-            anchor_spl::associated_token::Create {
-                payer: ctx.accounts.user.to_account_info(),
-                associated_token: ctx.accounts.user_nft_account.to_account_info(),
-                authority: ctx.accounts.user.to_account_info(),
-                mint: ctx.accounts.mint.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
+        reservation.seats = seats;
+        reservation.amount_paid = amount;
+        reservation.payout_claimed = false;
+        reservation.cancelled_by_organiser = false;
+        reservation.checked_in = false;
+
+        // Mint the booking ticket with the standard the organiser picked
+        match ctx.accounts.experience.ticket_mode {
+            TicketMode::Metaplex => metaplex_ticket::mint_ticket(&NewTicket::booked(&ctx))?,
+            TicketMode::Token2022 => token_2022_ticket::mint_ticket(&NewTicket::booked(&ctx))?,
+            TicketMode::Compressed => {
+                // compressed tickets have no mint, the reservation records the leaf instead
+                let ticket = compressed_ticket::mint_ticket(&NewTicket::booked(&ctx))?;
+                let reservation = &mut ctx.accounts.reservation;
+                reservation.nft_mint = ticket.asset_id;
+                reservation.ticket_nonce = ticket.nonce;
+                reservation.ticket_data_hash = ticket.data_hash;
+                reservation.ticket_creator_hash = ticket.creator_hash;
             }
-        );
-        anchor_spl::associated_token::create(cpi_ctx)?;
-
-        // Mint 1 token (NFT)
-        let cpi_ctx = CpiContext::new(ctx.accounts.token_program.to_account_info(), MintTo {
-            mint: ctx.accounts.mint.to_account_info(),
-            to: ctx.accounts.user_nft_account.to_account_info(),
-            authority: ctx.accounts.user.to_account_info(),
-        });
-        mint_to(cpi_ctx, 1)?;
-
-        // ✅ Use lightweight URI instead of full inline JSON
-        let metadata_uri = nft_metadata::create_metadata_uri(
-            &ctx.accounts.experience,
-            slot.start_time,
-            slot.end_time
-        );
-
-        let symbol = ctx.accounts.experience.title.chars().take(10).collect::<String>();
-        let ix = build_metadata_ix(&ctx, metadata_uri, symbol);
-
-        invoke_signed(
-            &ix,
-            &[
-                ctx.accounts.metadata_program.to_account_info(),
-                ctx.accounts.metadata.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.user_nft_account.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.token_program.to_account_info(),
-                ctx.accounts.rent.to_account_info(),
-                ctx.accounts.sysvar_instructions.to_account_info(),
-                ctx.accounts.master_edition.to_account_info(),
-            ],
-            &[] // Optional: seeds if authority is PDA
-        )?;
+        }
 
         emit!(ReservationCreated {
             user: ctx.accounts.user.key(),
             reservation: ctx.accounts.reservation.key(),
-            nft_mint: ctx.accounts.mint.key(),
+            nft_mint: ctx.accounts.reservation.nft_mint,
             start_time,
+            seats,
         });
 
         Ok(())
     }
 
     // function to create an experience
+    #[allow(clippy::too_many_arguments)]
     pub fn create_experience(
         ctx: Context<CreateExperience>,
         title: String,
         location: String,
         description: String,
-        price_lamports: u64
+        price_lamports: u64,
+        cancellation_policy: Option<CancellationPolicy>,
+        ticket_mode: Option<TicketMode>,
+        metadata_uri_template: Option<String>,
+        transferable: Option<bool>
     ) -> Result<()> {
         let experience = &mut ctx.accounts.experience;
+        let cancellation_policy = cancellation_policy.unwrap_or_default();
+        let metadata_uri_template = metadata_uri_template.unwrap_or_default();
 
-        require!(title.len() <= Experience::MAX_TITLE_LEN, ErrorCode::TitleTooLong);
-        require!(title.len() > 0, ErrorCode::TitleEmpty);
-        require!(location.len() <= Experience::MAX_LOCATION_LEN, ErrorCode::LocationTooLong);
-        require!(location.len() > 0, ErrorCode::LocationEmpty);
+        Experience::validate_title(&title)?;
+        Experience::validate_location(&location)?;
+        Experience::validate_description(&description)?;
+        Experience::validate_metadata_uri_template(&metadata_uri_template)?;
         require!(price_lamports > 0, ErrorCode::InvalidPrice);
+        cancellation_policy.validate()?;
 
         experience.organiser = ctx.accounts.organiser.key();
+        experience.title_seed = title.clone();
         experience.title = title;
         experience.description = description;
         experience.location = Some(location);
         experience.price_lamports = price_lamports;
-        experience.cancelation_fee_percent = 10; // Default 10% cancellation fee
+        experience.cancellation_policy = cancellation_policy;
+        experience.metadata_uri_template = metadata_uri_template;
         experience.time_slot_count = 0; // Initialize with 0 time slots
+        experience.next_slot_seq = 0;
+        experience.check_in_delegate = None;
+        experience.ticket_mode = ticket_mode.unwrap_or_default();
+        experience.merkle_tree = None;
+
+        // soulbound tickets rely on freezing or Token-2022, compressed leaves can't be locked
+        experience.transferable = transferable.unwrap_or(true);
+        experience.max_resale_markup_percent = 0;
+        experience.resale_royalty_percent = 0;
+        experience.ticket_royalties = TicketRoyalties::default();
+        require!(
+            experience.transferable || experience.ticket_mode != TicketMode::Compressed,
+            ErrorCode::InvalidTicketMode
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        vault.experience = experience.key();
+        vault.bump = ctx.bumps.vault;
+
+        let slot_schedule = &mut ctx.accounts.slot_schedule;
+        slot_schedule.experience = experience.key();
+        slot_schedule.buffer_seconds = 0;
+        slot_schedule.first_seq = None;
+
+        // experiences priced in an SPL token escrow payments in the vault's token account
+        experience.payment_mint = None;
+        if let Some(payment_mint) = &ctx.accounts.payment_mint {
+            let (Some(vault_payment_account), Some(token_program), Some(associated_token_program)) = (
+                &ctx.accounts.vault_payment_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.associated_token_program,
+            ) else {
+                return err!(ErrorCode::MissingPaymentAccounts);
+            };
+
+            let cpi_ctx = CpiContext::new(
+                associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: ctx.accounts.organiser.to_account_info(),
+                    associated_token: vault_payment_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                    mint: payment_mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: token_program.to_account_info(),
+                }
+            );
+            anchor_spl::associated_token::create(cpi_ctx)?;
+
+            experience.payment_mint = Some(payment_mint.key());
+        }
+
+        // Metaplex booking NFTs are verified into a collection NFT held by the program
+        experience.collection_mint = None;
+        if let Some(collection_mint) = &ctx.accounts.collection_mint {
+            require!(experience.ticket_mode == TicketMode::Metaplex, ErrorCode::InvalidTicketMode);
+            let collection_mint = collection_mint.key();
+
+            metaplex_ticket::create_collection(&ctx)?;
+            ctx.accounts.experience.collection_mint = Some(collection_mint);
+        }
 
+        let experience = &ctx.accounts.experience;
         emit!(ExperienceCreated {
             organiser: ctx.accounts.organiser.key(),
             experience: experience.key(),
@@ -176,370 +222,2840 @@ pub mod contract {
         Ok(())
     }
 
+    // function to update the details of an experience, only the provided fields change.
+    // The address stays derived from `title_seed`, so renaming doesn't move the experience
+    pub fn update_experience(
+        ctx: Context<UpdateExperience>,
+        title: Option<String>,
+        location: Option<String>,
+        description: Option<String>,
+        price_lamports: Option<u64>,
+        cancellation_policy: Option<CancellationPolicy>,
+        metadata_uri_template: Option<String>
+    ) -> Result<()> {
+        let experience = &mut ctx.accounts.experience;
+        let mut changed_fields = Vec::new();
+
+        if let Some(title) = title {
+            Experience::validate_title(&title)?;
+            experience.title = title;
+            changed_fields.push("title".to_string());
+        }
+
+        if let Some(location) = location {
+            Experience::validate_location(&location)?;
+            experience.location = Some(location);
+            changed_fields.push("location".to_string());
+        }
+
+        if let Some(description) = description {
+            Experience::validate_description(&description)?;
+            experience.description = description;
+            changed_fields.push("description".to_string());
+        }
+
+        if let Some(price_lamports) = price_lamports {
+            require!(price_lamports > 0, ErrorCode::InvalidPrice);
+            experience.price_lamports = price_lamports;
+            changed_fields.push("price_lamports".to_string());
+        }
+
+        if let Some(cancellation_policy) = cancellation_policy {
+            cancellation_policy.validate()?;
+            experience.cancellation_policy = cancellation_policy;
+            changed_fields.push("cancellation_policy".to_string());
+        }
+
+        if let Some(metadata_uri_template) = metadata_uri_template {
+            Experience::validate_metadata_uri_template(&metadata_uri_template)?;
+            experience.metadata_uri_template = metadata_uri_template;
+            changed_fields.push("metadata_uri_template".to_string());
+        }
+
+        require!(!changed_fields.is_empty(), ErrorCode::NothingToUpdate);
+
+        emit!(ExperienceUpdated {
+            organiser: ctx.accounts.organiser.key(),
+            experience: experience.key(),
+            changed_fields,
+        });
+
+        Ok(())
+    }
+
+    // function to register the merkle tree holding an experience's compressed tickets
+    pub fn create_ticket_tree(
+        ctx: Context<CreateTicketTree>,
+        max_depth: u32,
+        max_buffer_size: u32
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.experience.ticket_mode == TicketMode::Compressed,
+            ErrorCode::InvalidTicketMode
+        );
+        require!(ctx.accounts.experience.merkle_tree.is_none(), ErrorCode::InvalidTicketTree);
+
+        compressed_ticket::create_tree(&ctx, max_depth, max_buffer_size)?;
+        ctx.accounts.experience.merkle_tree = Some(ctx.accounts.merkle_tree.key());
+
+        Ok(())
+    }
+
     // function to add a time slot to an experience
     pub fn add_time_slot(
         ctx: Context<AddTimeSlot>,
         start_time: i64,
         end_time: i64,
-        price: u64
+        price: u64,
+        capacity: u16
     ) -> Result<()> {
         let slot = &mut ctx.accounts.slot;
         let experience = &mut ctx.accounts.experience;
 
         require!(start_time < end_time, ErrorCode::InvalidTimeSlot);
         require!(price > 0, ErrorCode::InvalidPrice);
-        require!(
-            experience.time_slot_count < Experience::MAX_TIME_SLOTS,
-            ErrorCode::TooManyTimeSlots
-        );
+        require!(capacity > 0, ErrorCode::InvalidTimeSlot);
 
         // adding the current time validation
         let current_time = Clock::get()?.unix_timestamp;
         require!(start_time > current_time, ErrorCode::InvalidTimeSlot);
 
-        // Initialize the new slot
+        // Initialize the new slot under the experience's next sequence number
+        let seq = experience.next_slot_seq;
         slot.experience = experience.key();
+        slot.seq = seq;
         slot.start_time = start_time;
         slot.end_time = end_time;
-        slot.is_booked = false;
         slot.price = price;
+        slot.capacity = capacity;
+        slot.booked_count = 0;
+        slot.is_cancelled = false;
+        slot.held_seats = 0;
+        slot.waitlist_head = 0;
+        slot.waitlist_tail = 0;
+
+        // keep the experience's sessions apart, only the slots either side of the new one need checking
+        let mut neighbours: Vec<&mut TimeSlotAccount> = ctx.accounts.prev_slot
+            .iter_mut()
+            .chain(ctx.accounts.next_slot.iter_mut())
+            .map(|neighbour| &mut **neighbour)
+            .collect();
+        ctx.accounts.slot_schedule.insert(&mut [&mut **slot], &mut neighbours)?;
+
+        // list it in the experience's slot registry
+        slot_registry::register(
+            &ctx.accounts.registry_page.to_account_info(),
+            &ctx.accounts.organiser.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &experience.key(),
+            ctx.program_id,
+            seq,
+            RegisteredSlot { start_time, end_time, removed: false }
+        )?;
 
         // Increment the time slot count
+        experience.next_slot_seq = seq.checked_add(1).ok_or(ErrorCode::AmountOverflow)?;
         experience.time_slot_count = experience.time_slot_count
             .checked_add(1)
-            .ok_or(ErrorCode::TooManyTimeSlots)?;
+            .ok_or(ErrorCode::AmountOverflow)?;
+
+        Ok(())
+    }
+
+    // function to add a series of time slots, the slot accounts, their registry pages and the existing
+    // slots around the series go in remaining_accounts
+    pub fn add_recurring_slots<'info>(
+        mut ctx: Context<'_, '_, '_, 'info, AddRecurringSlots<'info>>,
+        start_time: i64,
+        duration: i64,
+        interval: SlotInterval,
+        count: u8,
+        price: u64,
+        capacity: u16
+    ) -> Result<()> {
+        require!(price > 0, ErrorCode::InvalidPrice);
+        require!(capacity > 0, ErrorCode::InvalidTimeSlot);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(start_time > current_time, ErrorCode::InvalidTimeSlot);
+
+        let times = recurring_slots::slot_times(start_time, duration, interval, count)?;
+        recurring_slots::create_slots(&mut ctx, &times, price, capacity)?;
+
+        let experience = &mut ctx.accounts.experience;
+        experience.next_slot_seq = experience.next_slot_seq
+            .checked_add(count as u64)
+            .ok_or(ErrorCode::AmountOverflow)?;
+        experience.time_slot_count = experience.time_slot_count
+            .checked_add(count as u32)
+            .ok_or(ErrorCode::AmountOverflow)?;
+
+        Ok(())
+    }
+
+    // function to remove an unbooked time slot and reclaim its rent
+    pub fn remove_time_slot(ctx: Context<RemoveTimeSlot>, start_time: i64) -> Result<()> {
+        let experience = &mut ctx.accounts.experience;
+        ctx.accounts.slot_schedule.remove(
+            &ctx.accounts.slot,
+            ctx.accounts.prev_slot.as_deref_mut(),
+            ctx.accounts.next_slot.as_deref_mut()
+        )?;
+        ctx.accounts.registry_page.mark_removed(ctx.accounts.slot.seq)?;
+
+        // Decrement the time slot count
+        experience.time_slot_count = experience.time_slot_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::InvalidTimeSlot)?;
+
+        emit!(TimeSlotRemoved {
+            experience: experience.key(),
+            slot: ctx.accounts.slot.key(),
+            start_time,
+        });
 
         Ok(())
     }
 
-    pub fn cancel_reservation(ctx: Context<CancelReservation>) -> Result<()> {
+    pub fn cancel_reservation<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelReservation<'info>>,
+        _start_time: i64,
+        seats: Option<u16>,
+        ticket_root: Option<[u8; 32]>
+    ) -> Result<()> {
+        // whoever holds the ticket now may cancel, partly or in full
+        let holds_ticket = match ctx.accounts.experience.ticket_mode {
+            TicketMode::Compressed => {
+                compressed_ticket::verify_holder(&ctx, ticket_root)?;
+                true
+            }
+            _ =>
+                ctx.accounts.user_nft_account
+                    .as_ref()
+                    .is_some_and(|account| ctx.accounts.reservation.is_held_in(&ctx.accounts.user.key(), account)),
+        };
+        require!(holds_ticket, ErrorCode::Unauthorized);
+
         let reservation = &mut ctx.accounts.reservation;
         let slot = &mut ctx.accounts.slot;
         let experience = &ctx.accounts.experience;
 
-        // check if the reservation is too late to cancel
+        // pick the refund tier matching the notice given, too late if none applies. Bookers of a
+        // slot the organiser cancelled get everything back whenever they cancel
         let current_time = Clock::get()?.unix_timestamp;
-        let minimum_notice_period = 24 * 60 * 60; // 24 hours in seconds
+        let refund_percent = if slot.is_cancelled {
+            CancellationPolicy::MAX_REFUND_PERCENT
+        } else {
+            experience.cancellation_policy
+                .refund_percent(slot.start_time - current_time)
+                .ok_or(ErrorCode::TooLateToCancel)?
+        };
+
+        require!(reservation.is_active, ErrorCode::InvalidReservation);
+        reservation.user = ctx.accounts.user.key();
+
+        // the whole party unless only some of the seats are given up. The compressed leaf can't be
+        // rewritten with the smaller party, so those tickets are only cancelled as a whole
+        let seats = seats.unwrap_or(reservation.seats);
+        require!(seats > 0 && seats <= reservation.seats, ErrorCode::InvalidSeatCount);
         require!(
-            slot.start_time - current_time >= minimum_notice_period,
-            ErrorCode::TooLateToCancel
+            seats == reservation.seats || experience.ticket_mode != TicketMode::Compressed,
+            ErrorCode::InvalidTicketMode
         );
+        let released_amount = reservation.seat_amount(seats)?;
 
-        require!(reservation.is_active, ErrorCode::InvalidReservation);
-        require_keys_eq!(reservation.user, ctx.accounts.user.key(), ErrorCode::Unauthorized);
+        // split the escrowed payment into the refund and the organiser's fee
+        let (refund_amount, cancellation_fee) = vault::cancellation_split(
+            released_amount,
+            (CancellationPolicy::MAX_REFUND_PERCENT - refund_percent) as u64
+        )?;
 
-        // calculate cancellation fee (percentage of the price)
-        let cancellation_fee = (slot.price as u128)
-            .checked_mul(experience.cancelation_fee_percent as u128)
-            .unwrap()
-            .checked_div(100)
-            .unwrap() as u64;
-        let refund_amount = slot.price - cancellation_fee;
+        let escrow = vault::TokenEscrow::load(
+            experience,
+            &ctx.accounts.vault,
+            Some(&ctx.accounts.token_program),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
 
         // refund to user after deducting cancellation fee
-        invoke(
-            &system_instruction::transfer(
-                &experience.organiser.key(),
-                &ctx.accounts.user.key(),
-                refund_amount
-            ),
-            &[
-                experience.to_account_info(),
-                ctx.accounts.user.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
+        vault::pay_out(
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.user.to_account_info(),
+            ctx.accounts.user_payment_account.as_ref(),
+            refund_amount
         )?;
 
-        // transfer the cancellation fee to the organizer
-        invoke(
-            &system_instruction::transfer(
-                &ctx.accounts.user.key(),
-                &experience.organiser.key(),
-                cancellation_fee
-            ),
-            &[
-                ctx.accounts.user.to_account_info(),
-                experience.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ]
+        // transfer the cancellation fee to the organiser
+        vault::pay_out(
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.organiser.to_account_info(),
+            ctx.accounts.organiser_payment_account.as_ref(),
+            cancellation_fee
         )?;
 
-        slot.is_booked = false;
-        reservation.is_active = false;
+        // freed seats go to the waitlist first, the head of the queue gets an offer if passed in and a
+        // prepaid head is left for advance_waitlist to book
+        slot.free_seats(seats)?;
+        if let Some(entry) = &mut ctx.accounts.waitlist_entry {
+            if slot.offer_to(entry, current_time) {
+                emit!(WaitlistOfferMade {
+                    slot: slot.key(),
+                    user: entry.user,
+                    position: entry.position,
+                    offer_expires_at: entry.offer_expires_at.unwrap_or_default(),
+                });
+            }
+        }
+        reservation.seats -= seats;
+        reservation.amount_paid -= released_amount;
+
+        // Burn the booking ticket once no seats are left and close the reservation, the rent of both
+        // goes back to the holder and the slot can be booked again
+        let reservation_key = reservation.key();
+        if reservation.seats == 0 {
+            reservation.is_active = false;
+            match ctx.accounts.experience.ticket_mode {
+                TicketMode::Metaplex => metaplex_ticket::burn_ticket(&HeldTicket::cancelled(&ctx))?,
+                TicketMode::Token2022 => token_2022_ticket::burn_ticket(&HeldTicket::cancelled(&ctx))?,
+                TicketMode::Compressed => compressed_ticket::burn_ticket(&ctx, ticket_root)?,
+            }
+            ctx.accounts.reservation.close(ctx.accounts.user.to_account_info())?;
+        } else {
+            // the ticket shows the party size, rewrite it for the seats left
+            let seats_left = reservation.seats;
+            match ctx.accounts.experience.ticket_mode {
+                TicketMode::Metaplex =>
+                    metaplex_ticket::update_ticket(
+                        &HeldTicket::cancelled(&ctx),
+                        &reservation_key,
+                        &ctx.accounts.slot,
+                        seats_left
+                    )?,
+                TicketMode::Token2022 =>
+                    token_2022_ticket::update_ticket(
+                        &HeldTicket::cancelled(&ctx),
+                        &reservation_key,
+                        &ctx.accounts.slot,
+                        seats_left
+                    )?,
+                TicketMode::Compressed => {}
+            }
+        }
 
         emit!(ReservationCancelled {
             user: ctx.accounts.user.key(),
-            reservation: reservation.key(),
+            reservation: reservation_key,
+            seats,
             cancellation_fee,
         });
         Ok(())
     }
 
-    pub fn update_reservation(ctx: Context<UpdateReservation>, new_start_time: i64) -> Result<()> {
+    // function for the organiser to cancel a booked slot, called once per reservation and the ticket holder gets a full refund.
+    // remaining_accounts holds the `proof_len` proof nodes of a compressed ticket, then any waitlist entries to refund and close
+    pub fn organiser_cancel_slot<'info>(
+        ctx: Context<'_, '_, 'info, 'info, OrganiserCancelSlot<'info>>,
+        start_time: i64,
+        ticket_root: Option<[u8; 32]>,
+        proof_len: u8
+    ) -> Result<()> {
+        require!(ctx.remaining_accounts.len() >= proof_len as usize, ErrorCode::MissingTicketAccounts);
+        let (proof, waitlist_accounts) = ctx.remaining_accounts.split_at(proof_len as usize);
+
+        // refund whoever shows they hold the ticket now
+        let booker = ctx.accounts.booker.key();
+        let holds_ticket = match ctx.accounts.experience.ticket_mode {
+            TicketMode::Compressed => {
+                compressed_ticket::verify_booker(&ctx, proof, ticket_root)?;
+                true
+            }
+            _ =>
+                ctx.accounts.booker_nft_account
+                    .as_ref()
+                    .is_some_and(|account| ctx.accounts.reservation.is_held_in(&booker, account)),
+        };
+        require!(holds_ticket, ErrorCode::Unauthorized);
+
         let reservation = &mut ctx.accounts.reservation;
+        let experience = &mut ctx.accounts.experience;
+
+        require!(!reservation.payout_claimed, ErrorCode::PayoutAlreadyClaimed);
+
+        reservation.user = booker;
+
+        // full refund, no cancellation fee is kept
+        let refund_amount = reservation.amount_paid;
+        let escrow = vault::TokenEscrow::load(
+            experience,
+            &ctx.accounts.vault,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        vault::pay_out(
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.booker.to_account_info(),
+            ctx.accounts.booker_payment_account.as_ref(),
+            refund_amount
+        )?;
+
+        reservation.is_active = false;
+        reservation.amount_paid = 0;
+        reservation.cancelled_by_organiser = true;
+
+        // no new bookings once cancelled, the slot closes with the last refunded reservation
+        // once its waitlist has been refunded too
+        let slot = &mut ctx.accounts.slot;
+        slot.is_cancelled = true;
+        slot.release_seats(reservation.seats)?;
+        waitlist::refund_entries(
+            waitlist_accounts,
+            slot,
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            ctx.program_id
+        )?;
+        if slot.booked_count == 0 && !slot.has_waitlist() {
+            experience.time_slot_count = experience.time_slot_count
+                .checked_sub(1)
+                .ok_or(ErrorCode::InvalidTimeSlot)?;
+            ctx.accounts.slot_schedule.remove(
+                slot,
+                ctx.accounts.prev_slot.as_deref_mut(),
+                ctx.accounts.next_slot.as_deref_mut()
+            )?;
+            ctx.accounts.registry_page.mark_removed(slot.seq)?;
+            slot.close(ctx.accounts.organiser.to_account_info())?;
+        }
+
+        // burn the ticket when its holder signs along, otherwise void it so it stops looking like a
+        // live booking. A compressed leaf can only be burnt with its proof, the reservation is what
+        // check-in goes by for those
+        let burn = ctx.accounts.booker.is_signer;
+        let ticket = HeldTicket::organiser_cancelled(&ctx);
+        match (ticket.experience.ticket_mode, burn) {
+            (TicketMode::Metaplex, true) => metaplex_ticket::burn_ticket(&ticket)?,
+            (TicketMode::Metaplex, false) => metaplex_ticket::void_ticket(&ticket)?,
+            (TicketMode::Token2022, true) => token_2022_ticket::burn_ticket(&ticket)?,
+            (TicketMode::Token2022, false) => token_2022_ticket::void_ticket(&ticket)?,
+            (TicketMode::Compressed, _) => {}
+        }
+
+        emit!(SlotCancelledByOrganiser {
+            organiser: ctx.accounts.organiser.key(),
+            user: ctx.accounts.booker.key(),
+            reservation: ctx.accounts.reservation.key(),
+            start_time,
+            refund_amount,
+        });
+
+        Ok(())
+    }
+
+    // function to move a reservation to another slot of the same experience
+    pub fn update_reservation<'info>(
+        ctx: Context<'_, '_, '_, 'info, UpdateReservation<'info>>,
+        current_start_time: i64,
+        new_start_time: i64
+    ) -> Result<()> {
+        let reservation = &ctx.accounts.reservation;
         let old_slot = &mut ctx.accounts.old_slot;
         let new_slot = &mut ctx.accounts.new_slot;
 
-        require!(reservation.is_active, ErrorCode::AlreadyCancelled);
-        require_keys_eq!(reservation.user, ctx.accounts.user.key(), ErrorCode::Unauthorized);
-        require!(!new_slot.is_booked, ErrorCode::AlreadyBooked);
+        // only the current ticket holder may reschedule
+        let holds_ticket = ctx.accounts.user_nft_account
+            .as_ref()
+            .is_some_and(|account| reservation.is_held_in(&ctx.accounts.user.key(), account));
+        require!(holds_ticket, ErrorCode::Unauthorized);
 
-        // Free old slot
-        old_slot.is_booked = false;
+        require!(reservation.is_active, ErrorCode::AlreadyCancelled);
+        require!(current_start_time != new_start_time, ErrorCode::InvalidTimeSlot);
+        new_slot.book_seats(reservation.seats)?;
 
-        // Book new slot
-        new_slot.is_booked = true;
+        // both slots must still be far enough away to reschedule
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            old_slot.start_time - current_time >= Experience::RESCHEDULE_CUTOFF_SECONDS,
+            ErrorCode::TooLateToReschedule
+        );
+        require!(
+            new_slot.start_time - current_time >= Experience::RESCHEDULE_CUTOFF_SECONDS,
+            ErrorCode::TooLateToReschedule
+        );
 
-        // Update reservation data
-        reservation.time_slot = new_start_time;
-        reservation.start_time = new_slot.start_time;
-        reservation.end_time = new_slot.end_time;
+        // settle the price difference between the two slots through the vault
+        let amount_paid = reservation.amount_paid;
+        let new_price = new_slot.price
+            .checked_mul(reservation.seats as u64)
+            .ok_or(ErrorCode::AmountOverflow)?;
+        let escrow = vault::TokenEscrow::load(
+            &ctx.accounts.experience,
+            &ctx.accounts.vault,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        if new_price > amount_paid {
+            vault::deposit(
+                escrow.as_ref(),
+                &ctx.accounts.user,
+                ctx.accounts.user_payment_account.as_ref(),
+                &ctx.accounts.vault,
+                &ctx.accounts.system_program,
+                new_price - amount_paid
+            )?;
+        } else {
+            vault::pay_out(
+                escrow.as_ref(),
+                &ctx.accounts.vault,
+                &ctx.accounts.user.to_account_info(),
+                ctx.accounts.user_payment_account.as_ref(),
+                amount_paid - new_price
+            )?;
+        }
+
+        // Free the seats in the old slot
+        old_slot.free_seats(reservation.seats)?;
+
+        // Move the reservation to the PDA for the new start time
+        let new_reservation = &mut ctx.accounts.new_reservation;
+        new_reservation.experience_id = reservation.experience_id;
+        new_reservation.user = ctx.accounts.user.key();
+        new_reservation.booker = ctx.accounts.user.key();
+        new_reservation.time_slot = new_start_time;
+        new_reservation.nft_mint = reservation.nft_mint;
+        new_reservation.start_time = new_slot.start_time;
+        new_reservation.end_time = new_slot.end_time;
+        new_reservation.is_active = true;
+        new_reservation.seats = reservation.seats;
+        new_reservation.amount_paid = new_price;
+        new_reservation.payout_claimed = false;
+        new_reservation.cancelled_by_organiser = false;
+        new_reservation.checked_in = false;
+        new_reservation.ticket_nonce = reservation.ticket_nonce;
+        new_reservation.ticket_data_hash = reservation.ticket_data_hash;
+        new_reservation.ticket_creator_hash = reservation.ticket_creator_hash;
+
+        // Point the booking ticket at the new slot
+        match ctx.accounts.experience.ticket_mode {
+            TicketMode::Metaplex =>
+                metaplex_ticket::update_ticket(
+                    &HeldTicket::rescheduled(&ctx),
+                    &ctx.accounts.new_reservation.key(),
+                    &ctx.accounts.new_slot,
+                    ctx.accounts.new_reservation.seats
+                )?,
+            TicketMode::Token2022 =>
+                token_2022_ticket::update_ticket(
+                    &HeldTicket::rescheduled(&ctx),
+                    &ctx.accounts.new_reservation.key(),
+                    &ctx.accounts.new_slot,
+                    ctx.accounts.new_reservation.seats
+                )?,
+            // the compressed leaf can't be rewritten here, cancel and book the new slot instead
+            TicketMode::Compressed => {
+                return err!(ErrorCode::InvalidTicketMode);
+            }
+        }
 
         emit!(ReservationUpdated {
             user: ctx.accounts.user.key(),
-            reservation: reservation.key(),
+            reservation: ctx.accounts.new_reservation.key(),
             new_start_time,
         });
 
         Ok(())
     }
-}
 
-// context for booking a slot and minting an NFT
-#[derive(Accounts)]
-#[instruction(start_time: i64)]
-pub struct BookSlot<'info> {
-    #[account(mut)]
-    pub user: Signer<'info>,
+    // function to let the organiser hand door check-in rights to another wallet
+    pub fn set_check_in_delegate(
+        ctx: Context<SetCheckInDelegate>,
+        delegate: Option<Pubkey>
+    ) -> Result<()> {
+        ctx.accounts.experience.check_in_delegate = delegate;
+        Ok(())
+    }
 
-    #[account(mut)]
-    pub experience: Account<'info, Experience>,
+    // function to set the minimum gap between two sessions, only checked for slots added afterwards
+    pub fn set_slot_buffer(ctx: Context<SetSlotBuffer>, buffer_seconds: i64) -> Result<()> {
+        require!(buffer_seconds >= 0, ErrorCode::InvalidTimeSlot);
+        ctx.accounts.slot_schedule.buffer_seconds = buffer_seconds;
+        Ok(())
+    }
 
-    #[account(
-        mut,
-        seeds = [b"slot", experience.key().as_ref(), start_time.to_le_bytes().as_ref()],
-        bump,
-        constraint = !slot.is_booked @ ErrorCode::AlreadyBooked
-    )]
-    pub slot: Account<'info, TimeSlotAccount>,
+    // function to redeem a booking ticket at the door
+    pub fn check_in<'info>(
+        ctx: Context<'_, '_, '_, 'info, CheckIn<'info>>,
+        _start_time: i64,
+        ticket_root: Option<[u8; 32]>
+    ) -> Result<()> {
+        require!(!ctx.accounts.reservation.checked_in, ErrorCode::AlreadyCheckedIn);
 
-    #[account(
-        init,
-        payer = user,
-        space = 8 + Reservation::LEN,
-        seeds = [b"reservation", experience.key().as_ref(), start_time.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub reservation: Account<'info, Reservation>,
+        // compressed tickets have no token account, the leaf proves the signing guest holds it
+        let mut holder = None;
+        if ctx.accounts.experience.ticket_mode == TicketMode::Compressed {
+            compressed_ticket::verify_ticket(&ctx, ticket_root)?;
+            holder = ctx.accounts.holder.as_ref().map(|holder| holder.key());
+        }
 
-    /// CHECK: This is the mint account that will be initialized
-    #[account(mut)]
-    pub mint: UncheckedAccount<'info>,
+        let reservation = &mut ctx.accounts.reservation;
+        if let Some(holder) = holder {
+            reservation.user = holder;
+        }
 
-    /// CHECK: This is the user's NFT account that will be created
-    #[account(mut)]
-    pub user_nft_account: UncheckedAccount<'info>,
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            current_time >= reservation.start_time - Experience::CHECK_IN_WINDOW_SECONDS &&
+                current_time <= reservation.end_time,
+            ErrorCode::OutsideCheckInWindow
+        );
 
-    /// CHECK: This is the organiser's account
-    #[account(mut)]
-    pub organiser: AccountInfo<'info>,
+        reservation.checked_in = true;
 
-    /// CHECK: Metaplex validates this PDA internally
-    #[account(mut)]
-    pub master_edition: UncheckedAccount<'info>,
+        emit!(ReservationRedeemed {
+            user: reservation.user,
+            reservation: reservation.key(),
+            checked_in_by: ctx.accounts.authority.key(),
+            timestamp: current_time,
+        });
 
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+        Ok(())
+    }
 
-    /// CHECK: Safe, Metaplex metadata account
-    #[account(mut)]
-    pub metadata: UncheckedAccount<'info>,
+    // function to release an escrowed booking payment to the organiser once the slot has ended
+    pub fn claim_payout(ctx: Context<ClaimPayout>, _start_time: i64) -> Result<()> {
+        let reservation = &mut ctx.accounts.reservation;
 
-    /// CHECK: Safe, Metaplex program
-    pub metadata_program: UncheckedAccount<'info>,
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time >= reservation.end_time, ErrorCode::PayoutNotAvailable);
+        require!(!reservation.payout_claimed, ErrorCode::PayoutAlreadyClaimed);
 
-    /// CHECK: Required for Metaplex CPI
-    pub sysvar_instructions: UncheckedAccount<'info>,
-}
+        let amount = reservation.amount_paid;
+        let escrow = vault::TokenEscrow::load(
+            &ctx.accounts.experience,
+            &ctx.accounts.vault,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        vault::pay_out(
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.organiser.to_account_info(),
+            ctx.accounts.organiser_payment_account.as_ref(),
+            amount
+        )?;
 
-// context for creating an experience
-#[derive(Accounts)]
-#[instruction(title: String)]
-pub struct CreateExperience<'info> {
-    #[account(mut)]
-    pub organiser: Signer<'info>,
+        reservation.payout_claimed = true;
 
-    #[account(
-        init,
-        payer = organiser,
-        space = 8 + Experience::LEN,
-        seeds = [b"experience", organiser.key().as_ref(), title.as_bytes()],
-        bump
-    )]
-    pub experience: Account<'info, Experience>,
+        emit!(PayoutClaimed {
+            organiser: ctx.accounts.organiser.key(),
+            reservation: reservation.key(),
+            amount,
+        });
 
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-// context for the adding the time slot
-#[derive(Accounts)]
-#[instruction(start_time: i64)]
-pub struct AddTimeSlot<'info> {
-    #[account(mut)]
-    pub organiser: Signer<'info>,
+    // function to replace the cancellation policy of an experience
+    pub fn update_cancellation_policy(
+        ctx: Context<UpdateCancellationPolicy>,
+        cancellation_policy: CancellationPolicy
+    ) -> Result<()> {
+        cancellation_policy.validate()?;
 
-    #[account(mut, has_one = organiser)]
-    pub experience: Account<'info, Experience>,
+        let experience = &mut ctx.accounts.experience;
+        experience.cancellation_policy = cancellation_policy;
 
-    #[account(
-        init,
-        payer = organiser,
-        space = 8 + TimeSlotAccount::LEN,
-        seeds = [b"slot", experience.key().as_ref(), start_time.to_le_bytes().as_ref()],
-        bump
+        emit!(CancellationPolicyUpdated {
+            experience: experience.key(),
+            cancellation_policy: experience.cancellation_policy.clone(),
+        });
+
+        Ok(())
+    }
+
+    // function to set the royalty and co-host creators written into new booking NFTs
+    pub fn update_ticket_royalties(
+        ctx: Context<UpdateTicketRoyalties>,
+        ticket_royalties: TicketRoyalties
+    ) -> Result<()> {
+        let experience = &mut ctx.accounts.experience;
+        ticket_royalties.validate(&experience.organiser, &ctx.accounts.mint_authority.key())?;
+        experience.ticket_royalties = ticket_royalties;
+
+        emit!(TicketRoyaltiesUpdated {
+            experience: experience.key(),
+            ticket_royalties: experience.ticket_royalties.clone(),
+        });
+
+        Ok(())
+    }
+
+    // function for the organiser or a co-host to sign their creator entry on a Metaplex booking NFT,
+    // the program authority can only verify itself when the ticket is minted
+    pub fn verify_ticket_creator(ctx: Context<VerifyTicketCreator>, _start_time: i64) -> Result<()> {
+        metaplex_ticket::verify_creator(&ctx)?;
+
+        emit!(TicketCreatorVerified {
+            creator: ctx.accounts.creator.key(),
+            reservation: ctx.accounts.reservation.key(),
+        });
+
+        Ok(())
+    }
+
+    // function to set how far above the slot price tickets may be resold and the organiser's cut
+    pub fn set_resale_terms(
+        ctx: Context<SetResaleTerms>,
+        max_markup_percent: u16,
+        royalty_percent: u8
+    ) -> Result<()> {
+        require!(royalty_percent <= 100, ErrorCode::InvalidResaleTerms);
+
+        let experience = &mut ctx.accounts.experience;
+        experience.max_resale_markup_percent = max_markup_percent;
+        experience.resale_royalty_percent = royalty_percent;
+
+        emit!(ResaleTermsUpdated {
+            experience: experience.key(),
+            max_markup_percent,
+            royalty_percent,
+        });
+
+        Ok(())
+    }
+
+    // function to list a ticket for resale, the NFT is escrowed until it sells or is delisted
+    pub fn list_ticket(ctx: Context<ListTicket>, _start_time: i64, price: u64) -> Result<()> {
+        let experience = &ctx.accounts.experience;
+        let reservation = &ctx.accounts.reservation;
+
+        require!(experience.transferable, ErrorCode::TicketNotTransferable);
+        require!(
+            reservation.is_held_in(&ctx.accounts.seller.key(), &ctx.accounts.seller_nft_account),
+            ErrorCode::Unauthorized
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time < reservation.start_time, ErrorCode::ResaleClosed);
+
+        // the cap covers every seat on the ticket
+        let face_value = ctx.accounts.slot.price
+            .checked_mul(reservation.seats as u64)
+            .ok_or(ErrorCode::AmountOverflow)?;
+        require!(price > 0, ErrorCode::InvalidPrice);
+        require!(
+            price <= resale::max_resale_price(face_value, experience.max_resale_markup_percent)?,
+            ErrorCode::ResalePriceTooHigh
+        );
+
+        // move the ticket into the listing's escrow account
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.ticket_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.seller_nft_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.escrow_nft_account.to_account_info(),
+                    authority: ctx.accounts.seller.to_account_info(),
+                }
+            ),
+            1,
+            0
+        )?;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.seller = ctx.accounts.seller.key();
+        listing.reservation = reservation.key();
+        listing.price = price;
+        listing.bump = ctx.bumps.listing;
+
+        emit!(TicketListed {
+            seller: listing.seller,
+            reservation: listing.reservation,
+            price,
+        });
+
+        Ok(())
+    }
+
+    // function to take a ticket off the resale market and return it to the seller
+    pub fn delist_ticket(ctx: Context<DelistTicket>, _start_time: i64) -> Result<()> {
+        let reservation_key = ctx.accounts.reservation.key();
+        let listing_seeds: &[&[u8]] = &[b"listing", reservation_key.as_ref(), &[ctx.accounts.listing.bump]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticket_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.escrow_nft_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.seller_nft_account.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                &[listing_seeds]
+            ),
+            1,
+            0
+        )?;
+        token_interface::close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticket_token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: ctx.accounts.escrow_nft_account.to_account_info(),
+                    destination: ctx.accounts.seller.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                &[listing_seeds]
+            )
+        )?;
+
+        emit!(TicketDelisted {
+            seller: ctx.accounts.seller.key(),
+            reservation: reservation_key,
+        });
+
+        Ok(())
+    }
+
+    // function to buy a listed ticket, the seller is paid minus the organiser's royalty
+    pub fn buy_listed_ticket(ctx: Context<BuyListedTicket>, _start_time: i64) -> Result<()> {
+        let experience = &ctx.accounts.experience;
+        let price = ctx.accounts.listing.price;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(current_time < ctx.accounts.reservation.start_time, ErrorCode::ResaleClosed);
+
+        let (proceeds, royalty) = resale::resale_split(price, experience.resale_royalty_percent)?;
+        let payment = resale::ResalePayment::load(
+            experience,
+            &ctx.accounts.buyer,
+            &ctx.accounts.system_program,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.buyer_payment_account.as_ref()
+        )?;
+        payment.pay(
+            &ctx.accounts.seller.to_account_info(),
+            ctx.accounts.seller_payment_account.as_ref(),
+            proceeds
+        )?;
+        payment.pay(
+            &ctx.accounts.organiser.to_account_info(),
+            ctx.accounts.organiser_payment_account.as_ref(),
+            royalty
+        )?;
+
+        // release the escrowed ticket to the buyer
+        let reservation_key = ctx.accounts.reservation.key();
+        let listing_seeds: &[&[u8]] = &[b"listing", reservation_key.as_ref(), &[ctx.accounts.listing.bump]];
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticket_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.escrow_nft_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.buyer_nft_account.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                &[listing_seeds]
+            ),
+            1,
+            0
+        )?;
+        token_interface::close_account(
+            CpiContext::new_with_signer(
+                ctx.accounts.ticket_token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: ctx.accounts.escrow_nft_account.to_account_info(),
+                    destination: ctx.accounts.seller.to_account_info(),
+                    authority: ctx.accounts.listing.to_account_info(),
+                },
+                &[listing_seeds]
+            )
+        )?;
+
+        // the buyer now holds the booking
+        let buyer = ctx.accounts.buyer.key();
+        ctx.accounts.reservation.user = buyer;
+
+        emit!(TicketResold {
+            seller: ctx.accounts.seller.key(),
+            buyer,
+            reservation: reservation_key,
+            price,
+            royalty,
+        });
+
+        Ok(())
+    }
+
+    // function to queue for a fully booked slot, optionally paying up front to be booked automatically
+    // once the queue reaches the entry and enough seats are held
+    pub fn join_waitlist(
+        ctx: Context<JoinWaitlist>,
+        _start_time: i64,
+        seats: u16,
+        prepay: bool
+    ) -> Result<()> {
+        let slot = &mut ctx.accounts.slot;
+
+        require!(!slot.is_cancelled, ErrorCode::SlotCancelled);
+        require!(seats > 0 && seats <= slot.capacity, ErrorCode::InvalidSeatCount);
+        require!(!slot.has_free_seats(seats), ErrorCode::SeatsAvailable);
+
+        // prepaid entries are booked without the waitlister signing, which a soulbound Metaplex
+        // ticket needs to hand its token delegate to the program authority
+        let experience = &ctx.accounts.experience;
+        require!(
+            !prepay || experience.transferable || experience.ticket_mode != TicketMode::Metaplex,
+            ErrorCode::InvalidTicketMode
+        );
+
+        let deposit = if prepay {
+            slot.price.checked_mul(seats as u64).ok_or(ErrorCode::AmountOverflow)?
+        } else {
+            0
+        };
+        let escrow = vault::TokenEscrow::load(
+            &ctx.accounts.experience,
+            &ctx.accounts.vault,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        vault::deposit(
+            escrow.as_ref(),
+            &ctx.accounts.user,
+            ctx.accounts.user_payment_account.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.system_program,
+            deposit
+        )?;
+
+        let entry = &mut ctx.accounts.waitlist_entry;
+        entry.slot = slot.key();
+        entry.user = ctx.accounts.user.key();
+        entry.position = slot.waitlist_tail;
+        entry.seats = seats;
+        entry.deposit = deposit;
+        entry.offer_expires_at = None;
+        entry.withdrawn = false;
+
+        slot.waitlist_tail = slot.waitlist_tail.checked_add(1).ok_or(ErrorCode::AmountOverflow)?;
+
+        emit!(WaitlistJoined {
+            slot: slot.key(),
+            user: entry.user,
+            position: entry.position,
+            seats,
+            deposit,
+        });
+
+        Ok(())
+    }
+
+    // function to leave the waitlist, any prepaid amount is refunded in full
+    pub fn leave_waitlist(ctx: Context<LeaveWaitlist>, _start_time: i64, _position: u32) -> Result<()> {
+        let entry = &mut ctx.accounts.waitlist_entry;
+
+        let escrow = vault::TokenEscrow::load(
+            &ctx.accounts.experience,
+            &ctx.accounts.vault,
+            ctx.accounts.token_program.as_ref(),
+            ctx.accounts.payment_mint.as_ref(),
+            ctx.accounts.vault_payment_account.as_ref()
+        )?;
+        vault::pay_out(
+            escrow.as_ref(),
+            &ctx.accounts.vault,
+            &ctx.accounts.user.to_account_info(),
+            ctx.accounts.user_payment_account.as_ref(),
+            entry.deposit
+        )?;
+        entry.deposit = 0;
+        entry.withdrawn = true;
+
+        // the head of the queue is closed right away, later entries are closed when the queue reaches them
+        let slot = &mut ctx.accounts.slot;
+        if entry.position == slot.waitlist_head {
+            slot.pop_waitlist();
+            entry.close(ctx.accounts.user.to_account_info())?;
+        }
+
+        emit!(WaitlistLeft {
+            slot: slot.key(),
+            user: ctx.accounts.user.key(),
+            position: entry.position,
+        });
+
+        Ok(())
+    }
+
+    // function anyone can call to move the waitlist along, dropping a withdrawn or lapsed head entry
+    // or, once enough seats are held, booking a prepaid head and making anyone else an offer.
+    // A lapsed entry gets its prepaid amount back
+    pub fn advance_waitlist<'info>(
+        ctx: Context<'_, '_, '_, 'info, AdvanceWaitlist<'info>>,
+        start_time: i64
+    ) -> Result<()> {
+        let experience_key = ctx.accounts.experience.key();
+        let slot = &mut ctx.accounts.slot;
+        let entry = &mut ctx.accounts.waitlist_entry;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        // a prepaid head is booked at the price it paid, the caller funds the reservation and ticket
+        if entry.deposit > 0 && slot.can_seat(entry) {
+            let seats = entry.seats;
+            slot.held_seats -= seats;
+            slot.pop_waitlist();
+            slot.book_seats(seats)?;
+
+            let user = ctx.accounts.user.key();
+            let mut reservation = Reservation {
+                experience_id: experience_key,
+                user,
+                booker: user,
+                time_slot: start_time,
+                nft_mint: ctx.accounts.mint.as_ref().map_or(Pubkey::default(), |mint| mint.key()),
+                start_time: slot.start_time,
+                end_time: slot.end_time,
+                is_active: true,
+                seats,
+                amount_paid: entry.deposit,
+                payout_claimed: false,
+                cancelled_by_organiser: false,
+                checked_in: false,
+                ticket_nonce: 0,
+                ticket_data_hash: [0; 32],
+                ticket_creator_hash: [0; 32],
+            };
+
+            let reservation_info = ctx.accounts.reservation.to_account_info();
+            slot_registry::create_pda(
+                &reservation_info,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.program_id,
+                8 + Reservation::LEN,
+                &[
+                    b"reservation",
+                    experience_key.as_ref(),
+                    start_time.to_le_bytes().as_ref(),
+                    user.as_ref(),
+                    &[ctx.bumps.reservation],
+                ]
+            )?;
+
+            match ctx.accounts.experience.ticket_mode {
+                TicketMode::Metaplex => metaplex_ticket::mint_ticket(&NewTicket::waitlisted(&ctx))?,
+                TicketMode::Token2022 => token_2022_ticket::mint_ticket(&NewTicket::waitlisted(&ctx))?,
+                TicketMode::Compressed => {
+                    let ticket = compressed_ticket::mint_ticket(&NewTicket::waitlisted(&ctx))?;
+                    reservation.nft_mint = ticket.asset_id;
+                    reservation.ticket_nonce = ticket.nonce;
+                    reservation.ticket_data_hash = ticket.data_hash;
+                    reservation.ticket_creator_hash = ticket.creator_hash;
+                }
+            }
+            reservation.try_serialize(&mut &mut reservation_info.try_borrow_mut_data()?[..])?;
+
+            ctx.accounts.waitlist_entry.close(ctx.accounts.user.to_account_info())?;
+            emit!(ReservationCreated {
+                user,
+                reservation: reservation_info.key(),
+                nft_mint: reservation.nft_mint,
+                start_time,
+                seats,
+            });
+            return Ok(());
+        }
+
+        let lapsed = entry.offer_expires_at.is_some_and(|expires_at| current_time > expires_at);
+        if entry.withdrawn || lapsed {
+            if entry.deposit > 0 {
+                let escrow = vault::TokenEscrow::load(
+                    &ctx.accounts.experience,
+                    &ctx.accounts.vault,
+                    ctx.accounts.token_program.as_ref(),
+                    ctx.accounts.payment_mint.as_ref(),
+                    ctx.accounts.vault_payment_account.as_ref()
+                )?;
+                vault::pay_out(
+                    escrow.as_ref(),
+                    &ctx.accounts.vault,
+                    &ctx.accounts.user.to_account_info(),
+                    ctx.accounts.user_payment_account.as_ref(),
+                    entry.deposit
+                )?;
+                entry.deposit = 0;
+            }
+
+            // the held seats stay with the queue for the next entry
+            slot.pop_waitlist();
+            entry.close(ctx.accounts.user.to_account_info())?;
+            return Ok(());
+        }
+
+        require!(slot.offer_to(entry, current_time), ErrorCode::NoWaitlistOffer);
+        emit!(WaitlistOfferMade {
+            slot: slot.key(),
+            user: entry.user,
+            position: entry.position,
+            offer_expires_at: entry.offer_expires_at.unwrap_or_default(),
+        });
+
+        Ok(())
+    }
+}
+
+// context for booking a slot and minting an NFT
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct BookSlot<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    // only when claiming a waitlist offer
+    #[account(
+        mut,
+        close = user,
+        has_one = user,
+        constraint = waitlist_entry.slot == slot.key() @ ErrorCode::InvalidWaitlistEntry
+    )]
+    pub waitlist_entry: Option<Account<'info, WaitlistEntry>>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Reservation::LEN,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// CHECK: This is the mint account that will be initialized
+    #[account(mut)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: This is the user's NFT account that will be created
+    #[account(mut)]
+    pub user_nft_account: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA holding mint, freeze and update authority over booking NFTs
+    #[account(mut, seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// CHECK: Safe, Metaplex metadata account
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's collection mint, checked against the experience
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's merkle tree, checked against the experience and by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Bubblegum tree config PDA of the merkle tree
+    #[account(mut)]
+    pub tree_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, Bubblegum program
+    #[account(address = compressed_ticket::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, noop program used by Bubblegum for logging
+    #[account(address = compressed_ticket::NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+}
+
+// context for creating an experience
+#[derive(Accounts)]
+#[instruction(title: String)]
+pub struct CreateExperience<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(
+        init,
+        payer = organiser,
+        space = 8 + Experience::LEN,
+        seeds = [b"experience", organiser.key().as_ref(), title.as_bytes()],
+        bump
+    )]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        init,
+        payer = organiser,
+        space = 8 + Vault::LEN,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        init,
+        payer = organiser,
+        space = 8 + SlotSchedule::LEN,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    /// CHECK: Vault's associated token account for the payment mint, created by this instruction
+    #[account(mut)]
+    pub vault_payment_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Collection NFT mint, created by this instruction at the experience's PDA
+    #[account(mut, seeds = [b"collection", experience.key().as_ref()], bump)]
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program authority's associated token account for the collection mint, created by this instruction
+    #[account(mut)]
+    pub collection_token_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA holding the collection NFT and its authorities
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+}
+
+// context for updating an experience
+#[derive(Accounts)]
+pub struct UpdateExperience<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+}
+
+// context for registering the merkle tree for compressed tickets
+#[derive(Accounts)]
+pub struct CreateTicketTree<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    /// CHECK: Merkle tree allocated by the organiser and owned by the account compression program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    /// CHECK: Bubblegum tree config PDA, initialised by Bubblegum
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = compressed_ticket::BUBBLEGUM_PROGRAM_ID
+    )]
+    pub tree_config: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA that owns the tree and signs ticket mints
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Safe, Bubblegum program
+    #[account(address = compressed_ticket::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: Safe, noop program used by Bubblegum for logging
+    #[account(address = compressed_ticket::NOOP_PROGRAM_ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// context for the adding the time slot
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct AddTimeSlot<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        init,
+        payer = organiser,
+        space = 8 + TimeSlotAccount::LEN,
+        seeds = [b"slot", experience.key().as_ref(), experience.next_slot_seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    /// CHECK: Registry page the new slot is listed on, created once the previous page is full
+    #[account(
+        mut,
+        seeds = [
+            b"slot_registry",
+            experience.key().as_ref(),
+            SlotRegistryPage::page_of(experience.next_slot_seq).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub registry_page: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+
+    // the experience's slots just before and after the new one in start time order, when there are any
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), prev_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prev_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), next_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// context for adding a recurring series of time slots, the slot accounts follow in remaining_accounts
+#[derive(Accounts)]
+pub struct AddRecurringSlots<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// context for removing an unbooked time slot
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct RemoveTimeSlot<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        close = organiser,
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot,
+        constraint = slot.booked_count == 0 && !slot.has_waitlist() @ ErrorCode::AlreadyBooked
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+
+    // the slot's neighbours in start time order, relinked around it
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), prev_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prev_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), next_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"slot_registry",
+            experience.key().as_ref(),
+            SlotRegistryPage::page_of(slot.seq).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub registry_page: Account<'info, SlotRegistryPage>,
+}
+
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct CancelReservation<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(mut)]
+    pub organiser: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active,
+        constraint = !reservation.checked_in @ ErrorCode::AlreadyCheckedIn,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    // head of the slot's waitlist, offered the freed seats
+    #[account(
+        mut,
+        constraint = waitlist_entry.slot == slot.key() @ ErrorCode::InvalidWaitlistEntry
+    )]
+    pub waitlist_entry: Option<Account<'info, WaitlistEntry>>,
+
+    /// CHECK: Booking NFT mint recorded on the reservation
+    #[account(mut, address = reservation.nft_mint @ ErrorCode::InvalidReservation)]
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = user
+    )]
+    pub user_nft_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: Program PDA holding the close authority over Token-2022 tickets and thawing soulbound ones
+    #[account(mut, seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub organiser_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Program<'info, Token>,
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's merkle tree, checked against the experience and by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Bubblegum tree config PDA of the merkle tree
+    pub tree_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, Bubblegum program
+    #[account(address = compressed_ticket::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, noop program used by Bubblegum for logging
+    #[account(address = compressed_ticket::NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Delegate the holder has set on the compressed ticket leaf, the holder when absent
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+}
+
+// context for the organiser cancelling a booked slot
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct OrganiserCancelSlot<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active @ ErrorCode::InvalidReservation,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"slot_registry",
+            experience.key().as_ref(),
+            SlotRegistryPage::page_of(slot.seq).to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub registry_page: Account<'info, SlotRegistryPage>,
+
+    // the slot's neighbours in start time order, needed once the slot closes
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), prev_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prev_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), next_slot.seq.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub next_slot: Option<Account<'info, TimeSlotAccount>>,
+
+    // signs along when the booker wants the ticket burnt rather than voided
+    #[account(mut)]
+    pub booker: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub booker_nft_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: Booking NFT mint recorded on the reservation
+    #[account(mut, address = reservation.nft_mint @ ErrorCode::InvalidReservation)]
+    pub mint: UncheckedAccount<'info>,
+
+    /// CHECK: Program PDA holding the update and close authority over tickets
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub booker_payment_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's merkle tree, checked against the experience and by the compression program
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Delegate the holder has set on the compressed ticket leaf, the holder when absent
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(current_start_time: i64, new_start_time: i64)]
+pub struct UpdateReservation<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            current_start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active,
+        constraint = !reservation.checked_in @ ErrorCode::AlreadyCheckedIn,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + Reservation::LEN,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            new_start_time.to_le_bytes().as_ref(),
+            user.key().as_ref(),
+        ],
+        bump
+    )]
+    pub new_reservation: Account<'info, Reservation>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), old_slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = old_slot.start_time == current_start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub old_slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), new_slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = new_slot.start_time == new_start_time @ ErrorCode::InvalidTimeSlot,
+        constraint = new_slot.experience == experience.key() @ ErrorCode::InvalidTimeSlot
+    )]
+    pub new_slot: Account<'info, TimeSlotAccount>,
+
+    /// CHECK: Booking NFT mint recorded on the reservation
+    #[account(mut, address = reservation.nft_mint @ ErrorCode::InvalidReservation)]
+    pub mint: UncheckedAccount<'info>,
+
+    #[account(
+        token::mint = mint,
+        token::authority = user
+    )]
+    pub user_nft_account: Option<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA holding update authority over booking NFTs
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+}
+
+// context for setting the check-in delegate
+#[derive(Accounts)]
+pub struct SetCheckInDelegate<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+}
+
+// context for setting the buffer time between sessions
+#[derive(Accounts)]
+pub struct SetSlotBuffer<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"schedule", experience.key().as_ref()],
+        bump
+    )]
+    pub slot_schedule: Account<'info, SlotSchedule>,
+}
+
+// context for checking a guest in, signed by the organiser or the check-in delegate
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct CheckIn<'info> {
+    pub authority: Signer<'info>,
+
+    // guest holding a compressed ticket, signs to prove they are the leaf owner
+    pub holder: Option<Signer<'info>>,
+
+    #[account(
+        constraint = authority.key() == experience.organiser ||
+            Some(authority.key()) == experience.check_in_delegate @ ErrorCode::Unauthorized
+    )]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active @ ErrorCode::InvalidReservation,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// CHECK: Experience's merkle tree for compressed tickets
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Delegate the holder has set on the compressed ticket leaf, the holder when absent
+    pub leaf_delegate: Option<UncheckedAccount<'info>>,
+}
+
+// context for releasing an escrowed payment to the organiser
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct ClaimPayout<'info> {
+    #[account(mut)]
+    pub organiser: Signer<'info>,
+
+    #[account(has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active @ ErrorCode::InvalidReservation,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    // bookings of a cancelled slot are refunded, never paid out
+    #[account(
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot,
+        constraint = !slot.is_cancelled @ ErrorCode::SlotCancelled
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub organiser_payment_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+// context for changing the cancellation policy of an experience
+#[derive(Accounts)]
+pub struct UpdateCancellationPolicy<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+}
+
+// context for changing the royalties of an experience's booking NFTs
+#[derive(Accounts)]
+pub struct UpdateTicketRoyalties<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    /// CHECK: Program authority PDA, the verified creator of every booking NFT
+    #[account(seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+}
+
+// context for a creator signing their entry on a booking NFT
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct VerifyTicketCreator<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        constraint = experience.ticket_mode == TicketMode::Metaplex @ ErrorCode::InvalidTicketMode
+    )]
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    /// CHECK: Metadata of the reservation's booking NFT, Metaplex checks the creator against it
+    #[account(
+        mut,
+        address = mpl_token_metadata::accounts::Metadata::find_pda(&reservation.nft_mint).0 @ ErrorCode::InvalidReservation
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+// context for setting the resale terms of an experience
+#[derive(Accounts)]
+pub struct SetResaleTerms<'info> {
+    pub organiser: Signer<'info>,
+
+    #[account(mut, has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+}
+
+// context for listing a ticket for resale
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct ListTicket<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active @ ErrorCode::InvalidReservation,
+        constraint = !reservation.checked_in @ ErrorCode::AlreadyCheckedIn,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        address = reservation.nft_mint @ ErrorCode::InvalidReservation,
+        mint::token_program = ticket_token_program
+    )]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = seller,
+        token::token_program = ticket_token_program
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init,
+        payer = seller,
+        space = 8 + Listing::LEN,
+        seeds = [b"listing", reservation.key().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        init,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = listing,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub escrow_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub ticket_token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// context for taking a ticket off the resale market
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct DelistTicket<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        address = reservation.nft_mint @ ErrorCode::InvalidReservation,
+        mint::token_program = ticket_token_program
+    )]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", reservation.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = listing,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub escrow_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = seller,
+        associated_token::mint = mint,
+        associated_token::authority = seller,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub seller_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub ticket_token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// context for buying a listed ticket
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct BuyListedTicket<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(has_one = organiser)]
+    pub experience: Account<'info, Experience>,
+
+    #[account(mut)]
+    pub organiser: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            reservation.booker.as_ref(),
+        ],
+        bump,
+        constraint = reservation.is_active @ ErrorCode::InvalidReservation,
+        constraint = !reservation.checked_in @ ErrorCode::AlreadyCheckedIn,
+    )]
+    pub reservation: Account<'info, Reservation>,
+
+    #[account(
+        address = reservation.nft_mint @ ErrorCode::InvalidReservation,
+        mint::token_program = ticket_token_program
+    )]
+    pub mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    #[account(
+        mut,
+        close = seller,
+        seeds = [b"listing", reservation.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub seller: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = listing,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub escrow_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = ticket_token_program
+    )]
+    pub buyer_nft_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub buyer_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub seller_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub organiser_payment_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub ticket_token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// context for joining the waitlist of a fully booked slot
+#[derive(Accounts)]
+#[instruction(start_time: i64)]
+pub struct JoinWaitlist<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub experience: Account<'info, Experience>,
+
+    #[account(
+        mut,
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
+        bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8 + WaitlistEntry::LEN,
+        seeds = [b"waitlist", slot.key().as_ref(), slot.waitlist_tail.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
     )]
-    pub slot: Account<'info, TimeSlotAccount>,
+    pub vault: Account<'info, Vault>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
 
+    pub token_program: Option<Program<'info, Token>>,
     pub system_program: Program<'info, System>,
 }
 
+// context for leaving a slot's waitlist
 #[derive(Accounts)]
-#[instruction(start_time: i64)]
-pub struct CancelReservation<'info> {
+#[instruction(start_time: i64, position: u32)]
+pub struct LeaveWaitlist<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut)]
     pub experience: Account<'info, Experience>,
 
     #[account(
         mut,
-        seeds = [b"reservation", experience.key().as_ref(), start_time.to_le_bytes().as_ref()],
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
         bump,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
+    )]
+    pub slot: Account<'info, TimeSlotAccount>,
+
+    #[account(
+        mut,
         has_one = user,
-        constraint = reservation.is_active == true,
+        seeds = [b"waitlist", slot.key().as_ref(), position.to_le_bytes().as_ref()],
+        bump,
+        constraint = !waitlist_entry.withdrawn @ ErrorCode::InvalidWaitlistEntry
     )]
-    pub reservation: Account<'info, Reservation>,
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
 
     #[account(
         mut,
-        seeds = [b"slot", experience.key().as_ref(), start_time.to_le_bytes().as_ref()],
-        bump
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
     )]
-    pub slot: Account<'info, TimeSlotAccount>,
+    pub vault: Account<'info, Vault>,
 
-    pub system_program: Program<'info, System>,
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    pub token_program: Option<Program<'info, Token>>,
 }
 
+// context for moving a slot's waitlist along
 #[derive(Accounts)]
-#[instruction(current_start_time: i64, new_start_time: i64)]
-pub struct UpdateReservation<'info> {
+#[instruction(start_time: i64)]
+pub struct AdvanceWaitlist<'info> {
+    // funds the reservation and ticket of a prepaid head that gets booked
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
 
-    #[account(mut)]
     pub experience: Account<'info, Experience>,
 
     #[account(
         mut,
-        seeds = [b"reservation", experience.key().as_ref(), current_start_time.to_le_bytes().as_ref()],
+        seeds = [b"slot", experience.key().as_ref(), slot.seq.to_le_bytes().as_ref()],
         bump,
-        has_one = user,
-        constraint = reservation.is_active == true,
+        constraint = slot.start_time == start_time @ ErrorCode::InvalidTimeSlot
     )]
-    pub reservation: Account<'info, Reservation>,
+    pub slot: Account<'info, TimeSlotAccount>,
 
     #[account(
         mut,
-        seeds = [b"slot", experience.key().as_ref(), current_start_time.to_le_bytes().as_ref()],
+        has_one = user,
+        seeds = [b"waitlist", slot.key().as_ref(), slot.waitlist_head.to_le_bytes().as_ref()],
         bump
     )]
-    pub old_slot: Account<'info, TimeSlotAccount>,
+    pub waitlist_entry: Account<'info, WaitlistEntry>,
+
+    // receives the rent and any prepaid amount of a dropped entry, or the ticket of a booked one
+    #[account(mut)]
+    pub user: SystemAccount<'info>,
 
+    /// CHECK: Reservation of a prepaid head, created by the instruction when it is booked
     #[account(
         mut,
-        seeds = [b"slot", experience.key().as_ref(), new_start_time.to_le_bytes().as_ref()],
+        seeds = [
+            b"reservation",
+            experience.key().as_ref(),
+            start_time.to_le_bytes().as_ref(),
+            user.key().as_ref(),
+        ],
         bump
     )]
-    pub new_slot: Account<'info, TimeSlotAccount>,
+    pub reservation: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", experience.key().as_ref()],
+        bump = vault.bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    pub payment_mint: Option<Account<'info, Mint>>,
+
+    #[account(mut)]
+    pub user_payment_account: Option<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub vault_payment_account: Option<Account<'info, TokenAccount>>,
+
+    // the ticket accounts below are only needed when a prepaid head is booked
+    /// CHECK: This is the mint account that will be initialized
+    #[account(mut)]
+    pub mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: This is the user's NFT account that will be created
+    #[account(mut)]
+    pub user_nft_account: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Program PDA holding mint, freeze and update authority over booking NFTs
+    #[account(mut, seeds = [b"mint_authority"], bump)]
+    pub mint_authority: UncheckedAccount<'info>,
+
+    pub token_program: Option<Program<'info, Token>>,
+    pub ticket_token_program: Option<Program<'info, Token2022>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+    pub system_program: Program<'info, System>,
+    pub rent: Option<Sysvar<'info, Rent>>,
+
+    /// CHECK: Safe, Metaplex metadata account
+    #[account(mut)]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, Metaplex program
+    #[account(address = mpl_token_metadata::ID)]
+    pub metadata_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Required for Metaplex CPI
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's collection mint, checked against the experience
+    pub collection_mint: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    #[account(mut)]
+    pub collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Metaplex validates this PDA internally
+    pub collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Experience's merkle tree, checked against the experience and by Bubblegum
+    #[account(mut)]
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Bubblegum tree config PDA of the merkle tree
+    #[account(mut)]
+    pub tree_config: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, Bubblegum program
+    #[account(address = compressed_ticket::BUBBLEGUM_PROGRAM_ID)]
+    pub bubblegum_program: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, noop program used by Bubblegum for logging
+    #[account(address = compressed_ticket::NOOP_PROGRAM_ID)]
+    pub log_wrapper: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Safe, account compression program
+    #[account(address = compressed_ticket::ACCOUNT_COMPRESSION_PROGRAM_ID)]
+    pub compression_program: Option<UncheckedAccount<'info>>,
 }
 
 #[account]
 pub struct TimeSlotAccount {
     pub experience: Pubkey,
+    pub seq: u64, // position in the experience's slot sequence, part of the slot's seeds
     pub start_time: i64,
     pub end_time: i64,
-    pub is_booked: bool,
     pub price: u64,
-    pub booker: Option<Pubkey>,
+    pub capacity: u16, // seats on offer
+    pub booked_count: u16, // seats currently reserved
+    pub is_cancelled: bool, // cancelled by the organiser, closed once every booker is refunded
+    pub held_seats: u16, // freed seats kept for the waitlist
+    pub waitlist_head: u32, // position of the first waiting entry
+    pub waitlist_tail: u32, // position given to the next entry
+    pub prev_seq: Option<u64>, // previous slot of the experience in start time order
+    pub next_seq: Option<u64>, // next slot of the experience in start time order
 }
 
 impl TimeSlotAccount {
     pub const LEN: usize =
         8 + // discriminator
         32 + // experience (Pubkey)
+        8 + // seq (u64)
         8 + // start_time (i64)
         8 + // end_time (i64)
-        1 + // is_booked (bool)
         8 + // price (u64)
-        1 + // Option discriminator
-        32; // booker (Pubkey)
+        2 + // capacity (u16)
+        2 + // booked_count (u16)
+        1 + // is_cancelled (bool)
+        2 + // held_seats (u16)
+        4 + // waitlist_head (u32)
+        4 + // waitlist_tail (u32)
+        9 + // prev_seq (Option<u64>)
+        9; // next_seq (Option<u64>)
+
+    // whether `seats` more seats fit next to the booked and held ones
+    pub fn has_free_seats(&self, seats: u16) -> bool {
+        (self.booked_count as u32) + (self.held_seats as u32) + (seats as u32) <= (self.capacity as u32)
+    }
+
+    // takes `seats` seats, fails if they don't all fit or the slot is cancelled
+    pub fn book_seats(&mut self, seats: u16) -> Result<()> {
+        require!(!self.is_cancelled, ErrorCode::SlotCancelled);
+        require!(self.has_free_seats(seats), ErrorCode::AlreadyBooked);
+        self.booked_count += seats;
+        Ok(())
+    }
+
+    pub fn release_seats(&mut self, seats: u16) -> Result<()> {
+        self.booked_count = self.booked_count.checked_sub(seats).ok_or(ErrorCode::InvalidTimeSlot)?;
+        Ok(())
+    }
+
+    // releases booked seats, holding them for the waitlist while anyone is queued
+    pub fn free_seats(&mut self, seats: u16) -> Result<()> {
+        self.release_seats(seats)?;
+        if self.has_waitlist() && !self.is_cancelled {
+            self.held_seats += seats;
+        }
+        Ok(())
+    }
+
+    pub fn has_waitlist(&self) -> bool {
+        self.waitlist_head < self.waitlist_tail
+    }
+
+    // drops the head entry, held seats go back on sale once nobody is left waiting
+    pub fn pop_waitlist(&mut self) {
+        self.waitlist_head += 1;
+        if !self.has_waitlist() {
+            self.held_seats = 0;
+        }
+    }
+
+    // whether `entry` heads the queue and the held seats cover its party
+    pub fn can_seat(&self, entry: &WaitlistEntry) -> bool {
+        !self.is_cancelled &&
+            entry.position == self.waitlist_head &&
+            !entry.withdrawn &&
+            entry.seats <= self.held_seats
+    }
+
+    // offers the held seats to `entry` if it can be seated, prepaid entries are booked instead
+    pub fn offer_to(&self, entry: &mut WaitlistEntry, current_time: i64) -> bool {
+        if entry.deposit > 0 || entry.offer_expires_at.is_some() || !self.can_seat(entry) {
+            return false;
+        }
+
+        entry.offer_expires_at = Some(current_time.saturating_add(Experience::WAITLIST_OFFER_SECONDS));
+        true
+    }
+}
+
+// head of an experience's slots linked in start time order, sessions are kept apart by checking
+// each new slot against its neighbours only, so the number of slots is unbounded
+#[account]
+pub struct SlotSchedule {
+    pub experience: Pubkey,
+    pub buffer_seconds: i64, // minimum gap between two sessions
+    pub first_seq: Option<u64>, // earliest slot of the experience
+}
+
+impl SlotSchedule {
+    pub const LEN: usize =
+        32 + // experience
+        8 + // buffer_seconds
+        9; // first_seq
+
+    // links `new_slots` into the list, `neighbours` is the run of existing slots around them in list
+    // order. Fails if the run is broken, doesn't reach past the new slots or anything comes within the
+    // buffer of a new slot
+    pub fn insert(
+        &mut self,
+        new_slots: &mut [&mut TimeSlotAccount],
+        neighbours: &mut [&mut TimeSlotAccount]
+    ) -> Result<()> {
+        require!(
+            neighbours.windows(2).all(|pair| pair[0].next_seq == Some(pair[1].seq)),
+            ErrorCode::InvalidSlotAccount
+        );
+
+        // (is_new, index) of every slot in start time order
+        let mut order: Vec<(bool, usize)> = (0..new_slots.len())
+            .map(|i| (true, i))
+            .chain((0..neighbours.len()).map(|i| (false, i)))
+            .collect();
+        let slot_at = |&(is_new, i): &(bool, usize)| -> &TimeSlotAccount {
+            if is_new { &*new_slots[i] } else { &*neighbours[i] }
+        };
+        order.sort_by_key(|position| slot_at(position).start_time);
+
+        // a new slot at either end of the run must be at that end of the whole list
+        let list_empty = self.first_seq.is_none();
+        if order.first().is_some_and(|&(is_new, _)| is_new) {
+            let is_first = neighbours.first().map_or(list_empty, |neighbour| neighbour.prev_seq.is_none());
+            require!(is_first, ErrorCode::InvalidSlotAccount);
+        }
+        if order.last().is_some_and(|&(is_new, _)| is_new) {
+            let is_last = neighbours.last().map_or(list_empty, |neighbour| neighbour.next_seq.is_none());
+            require!(is_last, ErrorCode::InvalidSlotAccount);
+        }
+
+        let buffer = self.buffer_seconds;
+        let clear = order.windows(2).all(|pair| {
+            let (earlier, later) = (slot_at(&pair[0]), slot_at(&pair[1]));
+            !(pair[0].0 || pair[1].0) || earlier.end_time.saturating_add(buffer) <= later.start_time
+        });
+        require!(clear, ErrorCode::OverlappingTimeSlot);
+
+        let seqs: Vec<u64> = order.iter().map(|position| slot_at(position).seq).collect();
+        for (k, &(is_new, i)) in order.iter().enumerate() {
+            let slot = if is_new { &mut *new_slots[i] } else { &mut *neighbours[i] };
+            if k > 0 {
+                slot.prev_seq = Some(seqs[k - 1]);
+            } else if is_new {
+                slot.prev_seq = None;
+                self.first_seq = Some(slot.seq);
+            }
+            if k + 1 < seqs.len() {
+                slot.next_seq = Some(seqs[k + 1]);
+            } else if is_new {
+                slot.next_seq = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    // unlinks `slot` from the list, `prev` and `next` must be its current neighbours
+    pub fn remove(
+        &mut self,
+        slot: &TimeSlotAccount,
+        prev: Option<&mut TimeSlotAccount>,
+        next: Option<&mut TimeSlotAccount>
+    ) -> Result<()> {
+        require!(
+            prev.as_ref().map(|prev| prev.seq) == slot.prev_seq &&
+                next.as_ref().map(|next| next.seq) == slot.next_seq,
+            ErrorCode::InvalidSlotAccount
+        );
+
+        match prev {
+            Some(prev) => {
+                prev.next_seq = slot.next_seq;
+            }
+            None => {
+                self.first_seq = slot.next_seq;
+            }
+        }
+        if let Some(next) = next {
+            next.prev_seq = slot.prev_seq;
+        }
+
+        Ok(())
+    }
+}
+
+// registry entry of one slot, the slot account lives at seeds [b"slot", experience, seq]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegisteredSlot {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub removed: bool, // the slot account has been closed
+}
+
+// one page of an experience's slot registry, listing slots in sequence order so clients can
+// walk pages 0..=page_of(next_slot_seq - 1) instead of scanning program accounts
+#[account]
+pub struct SlotRegistryPage {
+    pub experience: Pubkey,
+    pub page: u32,
+    pub slots: Vec<RegisteredSlot>,
+}
+
+impl SlotRegistryPage {
+    pub const PAGE_SIZE: u64 = 64;
+    pub const LEN: usize =
+        32 + // experience
+        4 + // page
+        4 +
+        (Self::PAGE_SIZE as usize) * (8 + 8 + 1); // slots
+
+    pub fn page_of(seq: u64) -> u32 {
+        (seq / Self::PAGE_SIZE) as u32
+    }
+
+    pub fn index_of(seq: u64) -> usize {
+        (seq % Self::PAGE_SIZE) as usize
+    }
+
+    pub fn mark_removed(&mut self, seq: u64) -> Result<()> {
+        let entry = self.slots.get_mut(Self::index_of(seq)).ok_or(ErrorCode::InvalidRegistryPage)?;
+        entry.removed = true;
+        Ok(())
+    }
 }
 
 #[account]
 pub struct Experience {
     pub organiser: Pubkey,
     pub title: String,
+    pub title_seed: String, // title the experience was created with, part of its address
     pub description: String,
     pub location: Option<String>,
-    pub price_lamports: u64,
-    pub cancelation_fee_percent: u64,
-    pub time_slot_count: u8, // Track number of time slots
+    pub price_lamports: u64, // base units of `payment_mint` when one is set
+    pub cancellation_policy: CancellationPolicy,
+    pub metadata_uri_template: String, // per-booking metadata URI, default template when empty
+    pub time_slot_count: u32, // Track number of time slots
+    pub next_slot_seq: u64, // sequence number of the next slot, never reused
+    pub check_in_delegate: Option<Pubkey>, // door staff allowed to check guests in
+    pub payment_mint: Option<Pubkey>, // SPL token used for payments, lamports when None
+    pub ticket_mode: TicketMode, // token standard used for booking tickets
+    pub merkle_tree: Option<Pubkey>, // Bubblegum tree holding compressed tickets
+    pub collection_mint: Option<Pubkey>, // verified collection of the booking NFTs
+    pub transferable: bool, // soulbound tickets when false
+    pub max_resale_markup_percent: u16, // resale cap over the slot price
+    pub resale_royalty_percent: u8, // organiser's cut of each resale
+    pub ticket_royalties: TicketRoyalties, // royalty and creators on the booking NFTs
+}
+
+// token standard used to mint booking tickets for an experience
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TicketMode {
+    // Metaplex NonFungible with a master edition
+    #[default]
+    Metaplex,
+    // Token-2022 mint carrying its metadata in the metadata extension
+    Token2022,
+    // Bubblegum compressed NFT minted into the experience's merkle tree
+    Compressed,
 }
 
 impl Experience {
     pub const MAX_TITLE_LEN: usize = 24;
     pub const MAX_LOCATION_LEN: usize = 48;
     pub const MAX_DESCRIPTION_LEN: usize = 128;
-    pub const MAX_CANCELATION_FEE: u8 = 100;
-    pub const MAX_TIME_SLOTS: u8 = 10; // Maximum number of time slots allowed
+    pub const MAX_URI_LEN: usize = mpl_token_metadata::MAX_URI_LENGTH;
+    pub const RESCHEDULE_CUTOFF_SECONDS: i64 = 24 * 60 * 60; // no rescheduling in the last 24 hours
+    pub const CHECK_IN_WINDOW_SECONDS: i64 = 60 * 60; // check-in opens an hour before the start
+    pub const WAITLIST_OFFER_SECONDS: i64 = 2 * 60 * 60; // waitlist offers lapse after two hours
     pub const LEN: usize =
         8 + // discriminator
         32 + // organiser
         4 +
         Self::MAX_TITLE_LEN + // title
         4 +
+        Self::MAX_TITLE_LEN + // title_seed
+        4 +
         Self::MAX_DESCRIPTION_LEN + // description
         1 +
         4 +
         Self::MAX_LOCATION_LEN + // Option<String>
         8 + // price_lamports
-        8 + // cancellation_fee_percent
-        1; // time_slot_count
+        CancellationPolicy::LEN + // cancellation_policy
+        4 +
+        Self::MAX_URI_LEN + // metadata_uri_template
+        4 + // time_slot_count
+        8 + // next_slot_seq
+        1 +
+        32 + // check_in_delegate
+        1 +
+        32 + // payment_mint
+        1 + // ticket_mode
+        1 +
+        32 + // merkle_tree
+        1 +
+        32 + // collection_mint
+        1 + // transferable
+        2 + // max_resale_markup_percent
+        1 + // resale_royalty_percent
+        TicketRoyalties::LEN; // ticket_royalties
+
+    pub fn validate_title(title: &str) -> Result<()> {
+        require!(title.len() <= Self::MAX_TITLE_LEN, ErrorCode::TitleTooLong);
+        require!(!title.is_empty(), ErrorCode::TitleEmpty);
+        Ok(())
+    }
+
+    pub fn validate_location(location: &str) -> Result<()> {
+        require!(location.len() <= Self::MAX_LOCATION_LEN, ErrorCode::LocationTooLong);
+        require!(!location.is_empty(), ErrorCode::LocationEmpty);
+        Ok(())
+    }
+
+    pub fn validate_description(description: &str) -> Result<()> {
+        require!(description.len() <= Self::MAX_DESCRIPTION_LEN, ErrorCode::DescriptionTooLong);
+        Ok(())
+    }
+
+    pub fn validate_metadata_uri_template(template: &str) -> Result<()> {
+        require!(template.len() <= Self::MAX_URI_LEN, ErrorCode::MetadataUriTooLong);
+        Ok(())
+    }
 }
 
 #[account]
 pub struct Reservation {
     pub experience_id: Pubkey, // linked experience account
     pub user: Pubkey, // user who made the reservation
+    pub booker: Pubkey, // original booker, part of the reservation's seeds
     pub time_slot: i64, // index of the time slot in the experience account
     pub nft_mint: Pubkey,
     pub start_time: i64,
     pub end_time: i64,
     pub is_active: bool,
+    pub seats: u16, // party size covered by the ticket
+    pub amount_paid: u64, // lamports held in the vault for this booking
+    pub payout_claimed: bool,
+    pub cancelled_by_organiser: bool,
+    pub checked_in: bool,
+    pub ticket_nonce: u64, // leaf nonce of a compressed ticket
+    pub ticket_data_hash: [u8; 32],
+    pub ticket_creator_hash: [u8; 32],
 }
 
 impl Reservation {
-    const LEN: usize = 32 + 32 + 8 + 32 + 8 + 8 + 1;
+    const LEN: usize = 32 + 32 + 32 + 8 + 32 + 8 + 8 + 1 + 2 + 8 + 1 + 1 + 1 + 8 + 32 + 32;
+
+    // the booking NFT in `token_account` proves `holder` controls the reservation
+    pub fn is_held_in(&self, holder: &Pubkey, token_account: &token_interface::TokenAccount) -> bool {
+        token_account.mint == self.nft_mint && token_account.owner == *holder && token_account.amount == 1
+    }
+
+    // part of the escrowed payment covering `seats` of the party
+    pub fn seat_amount(&self, seats: u16) -> Result<u64> {
+        require!(seats <= self.seats, ErrorCode::InvalidSeatCount);
+        let amount = ((self.amount_paid as u128) * (seats as u128)) / (self.seats as u128);
+        u64::try_from(amount).map_err(|_| error!(ErrorCode::AmountOverflow))
+    }
+}
+
+// program-owned escrow holding booking payments for an experience
+#[account]
+pub struct Vault {
+    pub experience: Pubkey,
+    pub bump: u8,
+}
+
+impl Vault {
+    pub const LEN: usize = 32 + 1;
+}
+
+// a ticket on the resale market, its NFT sits in the listing's token account
+#[account]
+pub struct Listing {
+    pub seller: Pubkey,
+    pub reservation: Pubkey,
+    pub price: u64, // in the experience's payment currency
+    pub bump: u8,
+}
+
+impl Listing {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+// a place in a slot's waitlist, seeded by its position in the queue
+#[account]
+pub struct WaitlistEntry {
+    pub slot: Pubkey,
+    pub user: Pubkey,
+    pub position: u32,
+    pub seats: u16,
+    pub deposit: u64, // prepaid amount held in the vault, 0 when not prepaid
+    pub offer_expires_at: Option<i64>, // set once the held seats are offered
+    pub withdrawn: bool, // left the queue, closed when the queue reaches it
+}
+
+impl WaitlistEntry {
+    pub const LEN: usize = 32 + 32 + 4 + 2 + 8 + 9 + 1;
+
+    pub fn has_live_offer(&self, current_time: i64) -> bool {
+        !self.withdrawn && self.offer_expires_at.is_some_and(|expires_at| current_time <= expires_at)
+    }
 }
 
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid time slot provided.")]
     InvalidTimeSlot,
-    #[msg("The time slot is already booked.")]
+    #[msg("The time slot is fully booked.")]
     AlreadyBooked,
     #[msg("Insufficient funds")]
     InsufficientFunds,
@@ -559,10 +3075,70 @@ pub enum ErrorCode {
     TitleEmpty,
     #[msg("Location cannot be empty")]
     LocationEmpty,
-    #[msg("Maximum number of time slots reached")]
-    TooManyTimeSlots,
     #[msg("Too late to cancel reservation")]
     TooLateToCancel,
+    #[msg("Payout is only available after the slot has ended")]
+    PayoutNotAvailable,
+    #[msg("Payout already claimed")]
+    PayoutAlreadyClaimed,
+    #[msg("Cancellation fee must be between 0 and 100 percent")]
+    InvalidCancellationFee,
+    #[msg("Amount overflow")]
+    AmountOverflow,
+    #[msg("Invalid cancellation policy")]
+    InvalidCancellationPolicy,
+    #[msg("Description is too long")]
+    DescriptionTooLong,
+    #[msg("No fields provided to update")]
+    NothingToUpdate,
+    #[msg("Too late to reschedule reservation")]
+    TooLateToReschedule,
+    #[msg("Reservation already checked in")]
+    AlreadyCheckedIn,
+    #[msg("Check-in is only possible around the slot time")]
+    OutsideCheckInWindow,
+    #[msg("Token accounts are required for experiences priced in an SPL token")]
+    MissingPaymentAccounts,
+    #[msg("Payment mint does not match the experience")]
+    InvalidPaymentMint,
+    #[msg("Invalid payment token account")]
+    InvalidPaymentAccount,
+    #[msg("Ticket accounts for the experience's ticket mode are missing")]
+    MissingTicketAccounts,
+    #[msg("Instruction does not apply to the experience's ticket mode")]
+    InvalidTicketMode,
+    #[msg("Invalid merkle tree for compressed tickets")]
+    InvalidTicketTree,
+    #[msg("Collection mint does not match the experience")]
+    InvalidCollection,
+    #[msg("Metadata URI exceeds the Metaplex length limit")]
+    MetadataUriTooLong,
+    #[msg("Tickets for this experience cannot be transferred")]
+    TicketNotTransferable,
+    #[msg("Resale price exceeds the organiser's cap")]
+    ResalePriceTooHigh,
+    #[msg("Invalid resale terms")]
+    InvalidResaleTerms,
+    #[msg("Resale is closed once the slot has started")]
+    ResaleClosed,
+    #[msg("Invalid ticket royalties")]
+    InvalidTicketRoyalties,
+    #[msg("Slot account does not match its start time")]
+    InvalidSlotAccount,
+    #[msg("The time slot has been cancelled")]
+    SlotCancelled,
+    #[msg("Invalid number of seats")]
+    InvalidSeatCount,
+    #[msg("Seats are still available, book the slot instead")]
+    SeatsAvailable,
+    #[msg("Invalid waitlist entry")]
+    InvalidWaitlistEntry,
+    #[msg("No waitlist offer available")]
+    NoWaitlistOffer,
+    #[msg("Time slot overlaps another session of this experience")]
+    OverlappingTimeSlot,
+    #[msg("Registry page does not match the slot sequence")]
+    InvalidRegistryPage,
 }
 
 // event for experience creation
@@ -573,6 +3149,22 @@ pub struct ExperienceCreated {
     pub title: String,
 }
 
+// event for experience update
+#[event]
+pub struct ExperienceUpdated {
+    pub organiser: Pubkey,
+    pub experience: Pubkey,
+    pub changed_fields: Vec<String>,
+}
+
+// event for time slot removal
+#[event]
+pub struct TimeSlotRemoved {
+    pub experience: Pubkey,
+    pub slot: Pubkey,
+    pub start_time: i64,
+}
+
 // event for reservation creation
 #[event]
 pub struct ReservationCreated {
@@ -580,6 +3172,7 @@ pub struct ReservationCreated {
     pub reservation: Pubkey,
     pub nft_mint: Pubkey,
     pub start_time: i64,
+    pub seats: u16,
 }
 
 // event for reservation cancellation
@@ -587,9 +3180,20 @@ pub struct ReservationCreated {
 pub struct ReservationCancelled {
     pub user: Pubkey,
     pub reservation: Pubkey,
+    pub seats: u16,
     pub cancellation_fee: u64,
 }
 
+// event for a booked slot cancelled by the organiser
+#[event]
+pub struct SlotCancelledByOrganiser {
+    pub organiser: Pubkey,
+    pub user: Pubkey,
+    pub reservation: Pubkey,
+    pub start_time: i64,
+    pub refund_amount: u64,
+}
+
 // event for reservation update
 #[event]
 pub struct ReservationUpdated {
@@ -597,3 +3201,101 @@ pub struct ReservationUpdated {
     pub reservation: Pubkey,
     pub new_start_time: i64,
 }
+
+// event for payout release
+#[event]
+pub struct PayoutClaimed {
+    pub organiser: Pubkey,
+    pub reservation: Pubkey,
+    pub amount: u64,
+}
+
+// event for cancellation policy update
+#[event]
+pub struct CancellationPolicyUpdated {
+    pub experience: Pubkey,
+    pub cancellation_policy: CancellationPolicy,
+}
+
+// event for a ticket redeemed at the door
+#[event]
+pub struct ReservationRedeemed {
+    pub user: Pubkey,
+    pub reservation: Pubkey,
+    pub checked_in_by: Pubkey,
+    pub timestamp: i64,
+}
+
+// event for resale terms update
+#[event]
+pub struct ResaleTermsUpdated {
+    pub experience: Pubkey,
+    pub max_markup_percent: u16,
+    pub royalty_percent: u8,
+}
+
+// event for a ticket listed for resale
+#[event]
+pub struct TicketListed {
+    pub seller: Pubkey,
+    pub reservation: Pubkey,
+    pub price: u64,
+}
+
+// event for a ticket taken off the resale market
+#[event]
+pub struct TicketDelisted {
+    pub seller: Pubkey,
+    pub reservation: Pubkey,
+}
+
+// event for a resold ticket
+#[event]
+pub struct TicketResold {
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub reservation: Pubkey,
+    pub price: u64,
+    pub royalty: u64,
+}
+
+// event for ticket royalties update
+#[event]
+pub struct TicketRoyaltiesUpdated {
+    pub experience: Pubkey,
+    pub ticket_royalties: TicketRoyalties,
+}
+
+// event for a creator signing their entry on a booking NFT
+#[event]
+pub struct TicketCreatorVerified {
+    pub creator: Pubkey,
+    pub reservation: Pubkey,
+}
+
+// event for joining a waitlist
+#[event]
+pub struct WaitlistJoined {
+    pub slot: Pubkey,
+    pub user: Pubkey,
+    pub position: u32,
+    pub seats: u16,
+    pub deposit: u64,
+}
+
+// event for leaving a waitlist
+#[event]
+pub struct WaitlistLeft {
+    pub slot: Pubkey,
+    pub user: Pubkey,
+    pub position: u32,
+}
+
+// event for freed seats offered to the head of a waitlist
+#[event]
+pub struct WaitlistOfferMade {
+    pub slot: Pubkey,
+    pub user: Pubkey,
+    pub position: u32,
+    pub offer_expires_at: i64,
+}
@@ -0,0 +1,505 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{ invoke, invoke_signed };
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::{ approve, initialize_mint2, mint_to, spl_token, Approve, InitializeMint2, MintTo };
+use mpl_token_metadata::{ accounts::Metadata, types::Data };
+
+use crate::build_metadata::{
+    build_burn_ix,
+    build_collection_metadata_ix,
+    build_freeze_ix,
+    build_metadata_ix,
+    build_thaw_ix,
+    build_update_metadata_ix,
+    build_verify_collection_ix,
+    build_verify_creator_ix,
+    build_void_metadata_ix,
+};
+use crate::held_ticket::HeldTicket;
+use crate::new_ticket::NewTicket;
+use crate::{
+    nft_metadata,
+    CreateExperience,
+    ErrorCode,
+    TimeSlotAccount,
+    VerifyTicketCreator,
+};
+
+// creates the experience's collection NFT, held by and verified through the program authority PDA
+pub fn create_collection(ctx: &Context<CreateExperience>) -> Result<()> {
+    let (
+        Some(collection_mint),
+        Some(collection_metadata),
+        Some(collection_master_edition),
+        Some(collection_token_account),
+        Some(mint_authority),
+        Some(token_program),
+        Some(associated_token_program),
+        Some(metadata_program),
+        Some(sysvar_instructions),
+        Some(collection_bump),
+        Some(authority_bump),
+    ) = (
+        &ctx.accounts.collection_mint,
+        &ctx.accounts.collection_metadata,
+        &ctx.accounts.collection_master_edition,
+        &ctx.accounts.collection_token_account,
+        &ctx.accounts.mint_authority,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.metadata_program,
+        &ctx.accounts.sysvar_instructions,
+        ctx.bumps.collection_mint,
+        ctx.bumps.mint_authority,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    let experience_key = ctx.accounts.experience.key();
+    let collection_seeds: &[&[u8]] = &[b"collection", experience_key.as_ref(), &[collection_bump]];
+    let authority_seeds: &[&[u8]] = &[b"mint_authority", &[authority_bump]];
+
+    // Collection mint account, its address is a PDA of the experience
+    invoke_signed(
+        &system_instruction::create_account(
+            &ctx.accounts.organiser.key(),
+            &collection_mint.key(),
+            Rent::get()?.minimum_balance(82),
+            82,
+            &token_program.key()
+        ),
+        &[
+            ctx.accounts.organiser.to_account_info(),
+            collection_mint.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[collection_seeds]
+    )?;
+    initialize_mint2(
+        CpiContext::new(token_program.to_account_info(), InitializeMint2 {
+            mint: collection_mint.to_account_info(),
+        }),
+        0,
+        &mint_authority.key(),
+        Some(&mint_authority.key())
+    )?;
+
+    // the program authority PDA holds the single collection token
+    anchor_spl::associated_token::create(
+        CpiContext::new(
+            associated_token_program.to_account_info(),
+            anchor_spl::associated_token::Create {
+                payer: ctx.accounts.organiser.to_account_info(),
+                associated_token: collection_token_account.to_account_info(),
+                authority: mint_authority.to_account_info(),
+                mint: collection_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            }
+        )
+    )?;
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            MintTo {
+                mint: collection_mint.to_account_info(),
+                to: collection_token_account.to_account_info(),
+                authority: mint_authority.to_account_info(),
+            },
+            &[authority_seeds]
+        ),
+        1
+    )?;
+
+    let ix = build_collection_metadata_ix(
+        ctx,
+        collection_mint.key(),
+        collection_metadata.key(),
+        collection_master_edition.key(),
+        mint_authority.key(),
+        nft_metadata::create_collection_uri(&ctx.accounts.experience)
+    );
+    invoke_signed(
+        &ix,
+        &[
+            metadata_program.to_account_info(),
+            collection_metadata.to_account_info(),
+            collection_master_edition.to_account_info(),
+            collection_mint.to_account_info(),
+            mint_authority.to_account_info(),
+            ctx.accounts.organiser.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            sysvar_instructions.to_account_info(),
+            token_program.to_account_info(),
+        ],
+        &[authority_seeds]
+    )?;
+
+    Ok(())
+}
+
+// mints a Metaplex NonFungible booking ticket to the ticket's owner
+pub fn mint_ticket(ticket: &NewTicket) -> Result<()> {
+    let (
+        Some(mint),
+        Some(owner_nft_account),
+        Some(metadata),
+        Some(master_edition),
+        Some(token_program),
+        Some(associated_token_program),
+        Some(rent),
+        Some(metadata_program),
+        Some(sysvar_instructions),
+    ) = (
+        &ticket.mint,
+        &ticket.owner_nft_account,
+        &ticket.metadata,
+        &ticket.master_edition,
+        &ticket.token_program,
+        &ticket.associated_token_program,
+        &ticket.rent,
+        &ticket.metadata_program,
+        &ticket.sysvar_instructions,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    // Mint account creation
+    let mint_rent = Rent::get()?.minimum_balance(82);
+    invoke(
+        &system_instruction::create_account(
+            &ticket.payer.key(),
+            &mint.key(),
+            mint_rent,
+            82,
+            &token_program.key()
+        ),
+        &[ticket.payer.clone(), mint.clone(), ticket.system_program.clone()]
+    )?;
+
+    // Initialize mint, the program authority PDA holds mint and freeze authority
+    let authority_seeds: &[&[u8]] = &[b"mint_authority", &[ticket.mint_authority_bump]];
+    let mint_ix = spl_token::instruction::initialize_mint(
+        &token_program.key(),
+        &mint.key(),
+        &ticket.mint_authority.key(),
+        Some(&ticket.mint_authority.key()),
+        0
+    )?;
+    invoke(&mint_ix, &[mint.clone(), token_program.clone(), rent.clone()])?;
+
+    // Create ATA (associated token account)
+    let cpi_ctx = CpiContext::new(
+        associated_token_program.clone(),
+        anchor_spl::associated_token::Create {
+            payer: ticket.payer.clone(),
+            associated_token: owner_nft_account.clone(),
+            authority: ticket.owner.clone(),
+            mint: mint.clone(),
+            system_program: ticket.system_program.clone(),
+            token_program: token_program.clone(),
+        }
+    );
+    anchor_spl::associated_token::create(cpi_ctx)?;
+
+    // Mint 1 token (NFT)
+    let signer_seeds = &[authority_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        MintTo {
+            mint: mint.clone(),
+            to: owner_nft_account.clone(),
+            authority: ticket.mint_authority.clone(),
+        },
+        signer_seeds
+    );
+    mint_to(cpi_ctx, 1)?;
+
+    // ✅ Use lightweight URI instead of full inline JSON
+    let metadata_uri = nft_metadata::create_metadata_uri(
+        ticket.experience,
+        &ticket.reservation,
+        &mint.key(),
+        ticket.slot.start_time,
+        ticket.slot.end_time,
+        ticket.seats
+    )?;
+
+    let ix = build_metadata_ix(
+        ticket,
+        mint.key(),
+        token_program.key(),
+        metadata.key(),
+        master_edition.key(),
+        metadata_uri
+    );
+
+    invoke_signed(
+        &ix,
+        &[
+            metadata_program.clone(),
+            metadata.clone(),
+            mint.clone(),
+            ticket.payer.clone(),
+            owner_nft_account.clone(),
+            ticket.system_program.clone(),
+            token_program.clone(),
+            rent.clone(),
+            sysvar_instructions.clone(),
+            master_edition.clone(),
+            ticket.mint_authority.clone(),
+        ],
+        signer_seeds
+    )?;
+
+    // Verify the ticket into the experience's collection
+    if let Some(collection_mint) = ticket.experience.collection_mint {
+        let (
+            Some(collection_mint_account),
+            Some(collection_metadata),
+            Some(collection_master_edition),
+        ) = (
+            &ticket.collection_mint,
+            &ticket.collection_metadata,
+            &ticket.collection_master_edition,
+        ) else {
+            return err!(ErrorCode::MissingTicketAccounts);
+        };
+        require_keys_eq!(
+            collection_mint_account.key(),
+            collection_mint,
+            ErrorCode::InvalidCollection
+        );
+
+        let ix = build_verify_collection_ix(
+            ticket,
+            metadata.key(),
+            collection_mint,
+            collection_metadata.key(),
+            collection_master_edition.key()
+        );
+        invoke_signed(
+            &ix,
+            &[
+                metadata_program.clone(),
+                ticket.mint_authority.clone(),
+                metadata.clone(),
+                collection_mint_account.clone(),
+                collection_metadata.clone(),
+                collection_master_edition.clone(),
+                ticket.system_program.clone(),
+                sysvar_instructions.clone(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    // Soulbound tickets: the program authority becomes the token delegate and freezes the account
+    if !ticket.experience.transferable {
+        approve(
+            CpiContext::new(token_program.clone(), Approve {
+                to: owner_nft_account.clone(),
+                delegate: ticket.mint_authority.clone(),
+                authority: ticket.owner.clone(),
+            }),
+            1
+        )?;
+
+        let ix = build_freeze_ix(
+            ticket,
+            mint.key(),
+            owner_nft_account.key(),
+            token_program.key(),
+            master_edition.key()
+        );
+        invoke_signed(
+            &ix,
+            &[
+                metadata_program.clone(),
+                ticket.mint_authority.clone(),
+                owner_nft_account.clone(),
+                master_edition.clone(),
+                mint.clone(),
+                token_program.clone(),
+            ],
+            signer_seeds
+        )?;
+    }
+
+    Ok(())
+}
+
+// burns the ticket, Metaplex returns the account rent to the holder
+pub fn burn_ticket(ticket: &HeldTicket) -> Result<()> {
+    let (
+        Some(holder_nft_account),
+        Some(metadata),
+        Some(master_edition),
+        Some(token_program),
+        Some(metadata_program),
+        Some(sysvar_instructions),
+    ) = (
+        &ticket.holder_nft_account,
+        &ticket.metadata,
+        &ticket.master_edition,
+        &ticket.token_program,
+        &ticket.metadata_program,
+        &ticket.sysvar_instructions,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    // soulbound tickets are frozen, thaw them through the program authority first
+    if !ticket.experience.transferable {
+        let ix = build_thaw_ix(ticket, holder_nft_account.key(), master_edition.key(), token_program.key());
+        invoke_signed(
+            &ix,
+            &[
+                metadata_program.clone(),
+                ticket.mint_authority.clone(),
+                holder_nft_account.clone(),
+                master_edition.clone(),
+                ticket.mint.clone(),
+                token_program.clone(),
+            ],
+            &[&[b"mint_authority", &[ticket.mint_authority_bump]]]
+        )?;
+    }
+
+    // burning a verified collection item shrinks the collection size
+    let collection_metadata = match ticket.experience.collection_mint {
+        Some(_) => {
+            let Some(collection_metadata) = &ticket.collection_metadata else {
+                return err!(ErrorCode::MissingTicketAccounts);
+            };
+            Some(collection_metadata.clone())
+        }
+        None => None,
+    };
+
+    let ix = build_burn_ix(
+        ticket,
+        holder_nft_account.key(),
+        metadata.key(),
+        master_edition.key(),
+        collection_metadata.as_ref().map(|account| account.key()),
+        token_program.key()
+    );
+    let mut infos = vec![
+        metadata_program.clone(),
+        ticket.holder.clone(),
+        metadata.clone(),
+        master_edition.clone(),
+        ticket.mint.clone(),
+        holder_nft_account.clone(),
+        ticket.system_program.clone(),
+        sysvar_instructions.clone(),
+        token_program.clone()
+    ];
+    infos.extend(collection_metadata);
+    invoke(&ix, &infos)?;
+
+    Ok(())
+}
+
+// renames a ticket that can't be burnt so wallets and marketplaces show it is no longer valid
+pub fn void_ticket(ticket: &HeldTicket) -> Result<()> {
+    let (Some(metadata), Some(metadata_program), Some(sysvar_instructions)) = (
+        &ticket.metadata,
+        &ticket.metadata_program,
+        &ticket.sysvar_instructions,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    // keep everything else as minted, verified creators included
+    let current = Metadata::from_bytes(&metadata.try_borrow_data()?)?;
+    let data = Data {
+        name: nft_metadata::void_ticket_name(ticket.experience),
+        symbol: current.symbol.trim_end_matches('\0').to_string(),
+        uri: current.uri.trim_end_matches('\0').to_string(),
+        seller_fee_basis_points: current.seller_fee_basis_points,
+        creators: current.creators,
+    };
+    let ix = build_void_metadata_ix(ticket, metadata.key(), data);
+    invoke_signed(
+        &ix,
+        &[
+            metadata_program.clone(),
+            ticket.mint_authority.clone(),
+            ticket.mint.clone(),
+            metadata.clone(),
+            ticket.payer.clone(),
+            ticket.system_program.clone(),
+            sysvar_instructions.clone(),
+        ],
+        &[&[b"mint_authority", &[ticket.mint_authority_bump]]]
+    )?;
+
+    Ok(())
+}
+
+// points the ticket metadata at `reservation`'s slot and party size, after a reschedule or a
+// partial cancel
+pub fn update_ticket(
+    ticket: &HeldTicket,
+    reservation: &Pubkey,
+    slot: &TimeSlotAccount,
+    seats: u16
+) -> Result<()> {
+    let (Some(metadata), Some(metadata_program), Some(sysvar_instructions)) = (
+        &ticket.metadata,
+        &ticket.metadata_program,
+        &ticket.sysvar_instructions,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    let metadata_uri = nft_metadata::create_metadata_uri(
+        ticket.experience,
+        reservation,
+        &ticket.mint.key(),
+        slot.start_time,
+        slot.end_time,
+        seats
+    )?;
+    let current = Metadata::from_bytes(&metadata.try_borrow_data()?)?;
+    let verified_creators: Vec<Pubkey> = current.creators
+        .unwrap_or_default()
+        .iter()
+        .filter(|creator| creator.verified)
+        .map(|creator| creator.address)
+        .collect();
+    let ix = build_update_metadata_ix(ticket, metadata.key(), metadata_uri, &verified_creators);
+    invoke_signed(
+        &ix,
+        &[
+            metadata_program.clone(),
+            ticket.mint_authority.clone(),
+            ticket.mint.clone(),
+            metadata.clone(),
+            ticket.payer.clone(),
+            ticket.system_program.clone(),
+            sysvar_instructions.clone(),
+        ],
+        &[&[b"mint_authority", &[ticket.mint_authority_bump]]]
+    )?;
+
+    Ok(())
+}
+
+// signs the organiser's or a co-host's creator entry on a booking NFT
+pub fn verify_creator(ctx: &Context<VerifyTicketCreator>) -> Result<()> {
+    let ix = build_verify_creator_ix(ctx);
+    invoke(
+        &ix,
+        &[
+            ctx.accounts.metadata_program.to_account_info(),
+            ctx.accounts.creator.to_account_info(),
+            ctx.accounts.metadata.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.sysvar_instructions.to_account_info(),
+        ]
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+use crate::{ AdvanceWaitlist, BookSlot, Experience, TimeSlotAccount };
+
+// the booking NFT of a new reservation, gathered from whichever instruction books the seats so a
+// booker and an automatically booked waitlister get their ticket through the same helpers
+pub struct NewTicket<'a, 'info> {
+    pub experience: &'a Account<'info, Experience>,
+    pub slot: &'a TimeSlotAccount,
+    pub reservation: Pubkey,
+    pub seats: u16,
+    // receives the ticket, signs for the delegate of a soulbound Metaplex ticket
+    pub owner: AccountInfo<'info>,
+    // funds the mint, the owner's token account and the metadata
+    pub payer: AccountInfo<'info>,
+    pub owner_nft_account: Option<AccountInfo<'info>>,
+    pub mint: Option<AccountInfo<'info>>,
+    pub mint_authority: AccountInfo<'info>,
+    pub mint_authority_bump: u8,
+    pub metadata: Option<AccountInfo<'info>>,
+    pub master_edition: Option<AccountInfo<'info>>,
+    pub collection_mint: Option<AccountInfo<'info>>,
+    pub collection_metadata: Option<AccountInfo<'info>>,
+    pub collection_master_edition: Option<AccountInfo<'info>>,
+    pub token_program: Option<AccountInfo<'info>>,
+    pub ticket_token_program: Option<AccountInfo<'info>>,
+    pub associated_token_program: Option<AccountInfo<'info>>,
+    pub system_program: AccountInfo<'info>,
+    pub rent: Option<AccountInfo<'info>>,
+    pub metadata_program: Option<AccountInfo<'info>>,
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+    pub merkle_tree: Option<AccountInfo<'info>>,
+    pub tree_config: Option<AccountInfo<'info>>,
+    pub bubblegum_program: Option<AccountInfo<'info>>,
+    pub log_wrapper: Option<AccountInfo<'info>>,
+    pub compression_program: Option<AccountInfo<'info>>,
+}
+
+impl<'a, 'info> NewTicket<'a, 'info> {
+    pub fn booked(ctx: &'a Context<'_, '_, '_, 'info, BookSlot<'info>>) -> Self {
+        let accounts = &ctx.accounts;
+        Self {
+            experience: &accounts.experience,
+            slot: &accounts.slot,
+            reservation: accounts.reservation.key(),
+            seats: accounts.reservation.seats,
+            owner: accounts.user.to_account_info(),
+            payer: accounts.user.to_account_info(),
+            owner_nft_account: Some(accounts.user_nft_account.to_account_info()),
+            mint: Some(accounts.mint.to_account_info()),
+            mint_authority: accounts.mint_authority.to_account_info(),
+            mint_authority_bump: ctx.bumps.mint_authority,
+            metadata: accounts.metadata.as_ref().map(|account| account.to_account_info()),
+            master_edition: accounts.master_edition.as_ref().map(|account| account.to_account_info()),
+            collection_mint: accounts.collection_mint.as_ref().map(|account| account.to_account_info()),
+            collection_metadata: accounts.collection_metadata.as_ref().map(|account| account.to_account_info()),
+            collection_master_edition: accounts.collection_master_edition
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            token_program: Some(accounts.token_program.to_account_info()),
+            ticket_token_program: accounts.ticket_token_program.as_ref().map(|program| program.to_account_info()),
+            associated_token_program: Some(accounts.associated_token_program.to_account_info()),
+            system_program: accounts.system_program.to_account_info(),
+            rent: Some(accounts.rent.to_account_info()),
+            metadata_program: accounts.metadata_program.as_ref().map(|program| program.to_account_info()),
+            sysvar_instructions: accounts.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+            merkle_tree: accounts.merkle_tree.as_ref().map(|account| account.to_account_info()),
+            tree_config: accounts.tree_config.as_ref().map(|account| account.to_account_info()),
+            bubblegum_program: accounts.bubblegum_program.as_ref().map(|program| program.to_account_info()),
+            log_wrapper: accounts.log_wrapper.as_ref().map(|program| program.to_account_info()),
+            compression_program: accounts.compression_program.as_ref().map(|program| program.to_account_info()),
+        }
+    }
+
+    pub fn waitlisted(ctx: &'a Context<'_, '_, '_, 'info, AdvanceWaitlist<'info>>) -> Self {
+        let accounts = &ctx.accounts;
+        Self {
+            experience: &accounts.experience,
+            slot: &accounts.slot,
+            reservation: accounts.reservation.key(),
+            seats: accounts.waitlist_entry.seats,
+            owner: accounts.user.to_account_info(),
+            payer: accounts.payer.to_account_info(),
+            owner_nft_account: accounts.user_nft_account.as_ref().map(|account| account.to_account_info()),
+            mint: accounts.mint.as_ref().map(|account| account.to_account_info()),
+            mint_authority: accounts.mint_authority.to_account_info(),
+            mint_authority_bump: ctx.bumps.mint_authority,
+            metadata: accounts.metadata.as_ref().map(|account| account.to_account_info()),
+            master_edition: accounts.master_edition.as_ref().map(|account| account.to_account_info()),
+            collection_mint: accounts.collection_mint.as_ref().map(|account| account.to_account_info()),
+            collection_metadata: accounts.collection_metadata.as_ref().map(|account| account.to_account_info()),
+            collection_master_edition: accounts.collection_master_edition
+                .as_ref()
+                .map(|account| account.to_account_info()),
+            token_program: accounts.token_program.as_ref().map(|program| program.to_account_info()),
+            ticket_token_program: accounts.ticket_token_program.as_ref().map(|program| program.to_account_info()),
+            associated_token_program: accounts.associated_token_program
+                .as_ref()
+                .map(|program| program.to_account_info()),
+            system_program: accounts.system_program.to_account_info(),
+            rent: accounts.rent.as_ref().map(|rent| rent.to_account_info()),
+            metadata_program: accounts.metadata_program.as_ref().map(|program| program.to_account_info()),
+            sysvar_instructions: accounts.sysvar_instructions.as_ref().map(|account| account.to_account_info()),
+            merkle_tree: accounts.merkle_tree.as_ref().map(|account| account.to_account_info()),
+            tree_config: accounts.tree_config.as_ref().map(|account| account.to_account_info()),
+            bubblegum_program: accounts.bubblegum_program.as_ref().map(|program| program.to_account_info()),
+            log_wrapper: accounts.log_wrapper.as_ref().map(|program| program.to_account_info()),
+            compression_program: accounts.compression_program.as_ref().map(|program| program.to_account_info()),
+        }
+    }
+}
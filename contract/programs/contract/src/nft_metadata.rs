@@ -1,14 +1,55 @@
 use anchor_lang::prelude::*;
+use url::form_urlencoded;
 
-use crate::Experience;
+use crate::{ ErrorCode, Experience };
 
+// Used for experiences that have not set a URI template of their own
+const TEMPLATE_URI: &str = "https://raw.githubusercontent.com/JkrishnaD/slot-mint-asset/main/metadata/template.json";
+
+// renders the experience's URI template for one booking, e.g.
+// `https://example.com/tickets/{reservation}.json?start={start_time}&seats={seats}&where={location}`
 pub fn create_metadata_uri(
     experience: &Experience,
+    reservation: &Pubkey,
+    mint: &Pubkey,
     start_time: i64,
     end_time: i64,
-) -> String {
-    // Use a single template file for all bookings
-    String::from("https://raw.githubusercontent.com/JkrishnaD/slot-mint-asset/main/metadata/template.json")
+    seats: u16
+) -> Result<String> {
+    if experience.metadata_uri_template.is_empty() {
+        return Ok(String::from(TEMPLATE_URI));
+    }
+
+    let location = experience.location.as_deref().unwrap_or_default();
+    let uri = experience.metadata_uri_template
+        .replace("{reservation}", &reservation.to_string())
+        .replace("{mint}", &mint.to_string())
+        .replace("{start_time}", &start_time.to_string())
+        .replace("{end_time}", &end_time.to_string())
+        .replace("{seats}", &seats.to_string())
+        .replace("{location}", &form_urlencoded::byte_serialize(location.as_bytes()).collect::<String>());
+
+    require!(uri.len() <= Experience::MAX_URI_LEN, ErrorCode::MetadataUriTooLong);
+    Ok(uri)
+}
+
+// names of an experience's NFTs, every suffix is at most 8 characters so a title of
+// Experience::MAX_TITLE_LEN still fits Metaplex's 32 character name limit
+pub fn ticket_name(experience: &Experience) -> String {
+    format!("{} Booking", experience.title)
+}
+
+pub fn collection_name(experience: &Experience) -> String {
+    format!("{} Tickets", experience.title)
+}
+
+// a ticket that was voided instead of burnt
+pub fn void_ticket_name(experience: &Experience) -> String {
+    format!("{} (void)", experience.title)
+}
+
+pub fn create_collection_uri(_experience: &Experience) -> String {
+    String::from(TEMPLATE_URI)
 }
 
 #[error_code]
@@ -0,0 +1,138 @@
+use anchor_lang::prelude::*;
+
+use crate::{ slot_registry, AddRecurringSlots, ErrorCode, RegisteredSlot, SlotRegistryPage, TimeSlotAccount };
+
+// how far apart consecutive slots of a recurring series start
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotInterval {
+    Daily,
+    Weekly,
+    Custom { seconds: i64 },
+}
+
+impl SlotInterval {
+    pub fn seconds(&self) -> i64 {
+        match self {
+            SlotInterval::Daily => 24 * 60 * 60,
+            SlotInterval::Weekly => 7 * 24 * 60 * 60,
+            SlotInterval::Custom { seconds } => *seconds,
+        }
+    }
+}
+
+// (start_time, end_time) of every slot in the series, slots of one series never overlap
+pub fn slot_times(
+    start_time: i64,
+    duration: i64,
+    interval: SlotInterval,
+    count: u8
+) -> Result<Vec<(i64, i64)>> {
+    require!(count > 0, ErrorCode::InvalidTimeSlot);
+    require!(duration > 0, ErrorCode::InvalidTimeSlot);
+    require!(interval.seconds() >= duration, ErrorCode::InvalidTimeSlot);
+
+    (0..count as i64)
+        .map(|i| {
+            let start = interval
+                .seconds()
+                .checked_mul(i)
+                .and_then(|offset| start_time.checked_add(offset))
+                .ok_or(ErrorCode::InvalidTimeSlot)?;
+            let end = start.checked_add(duration).ok_or(ErrorCode::InvalidTimeSlot)?;
+            Ok((start, end))
+        })
+        .collect()
+}
+
+// creates one slot PDA per entry of `times` and registers it, remaining_accounts holds the slot
+// accounts in sequence order, every registry page the series lands on and then the run of existing
+// slots around the series in start time order
+pub fn create_slots<'info>(
+    ctx: &mut Context<'_, '_, '_, 'info, AddRecurringSlots<'info>>,
+    times: &[(i64, i64)],
+    price: u64,
+    capacity: u16
+) -> Result<()> {
+    let first_seq = ctx.accounts.experience.next_slot_seq;
+    let last_seq = first_seq + (times.len() as u64) - 1;
+    let page_count = (SlotRegistryPage::page_of(last_seq) - SlotRegistryPage::page_of(first_seq) + 1) as usize;
+    require!(
+        ctx.remaining_accounts.len() >= times.len() + page_count,
+        ErrorCode::InvalidSlotAccount
+    );
+    let (slot_infos, rest) = ctx.remaining_accounts.split_at(times.len());
+    let (page_infos, neighbour_infos) = rest.split_at(page_count);
+
+    let experience_key = ctx.accounts.experience.key();
+    let mut slots: Vec<TimeSlotAccount> = (first_seq..)
+        .zip(times)
+        .map(|(seq, &(start_time, end_time))| TimeSlotAccount {
+            experience: experience_key,
+            seq,
+            start_time,
+            end_time,
+            price,
+            capacity,
+            booked_count: 0,
+            is_cancelled: false,
+            held_seats: 0,
+            waitlist_head: 0,
+            waitlist_tail: 0,
+            prev_seq: None,
+            next_seq: None,
+        })
+        .collect();
+
+    // link the series in between the existing slots, keeping every session apart
+    let mut neighbours = neighbour_infos
+        .iter()
+        .map(|info| {
+            require_keys_eq!(*info.owner, *ctx.program_id, ErrorCode::InvalidSlotAccount);
+            let neighbour = TimeSlotAccount::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(neighbour.experience, experience_key, ErrorCode::InvalidSlotAccount);
+            Ok(neighbour)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    ctx.accounts.slot_schedule.insert(
+        &mut slots.iter_mut().collect::<Vec<_>>(),
+        &mut neighbours.iter_mut().collect::<Vec<_>>()
+    )?;
+    for (info, neighbour) in neighbour_infos.iter().zip(&neighbours) {
+        neighbour.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    }
+
+    let space = 8 + TimeSlotAccount::LEN;
+
+    for (slot_info, slot) in slot_infos.iter().zip(&slots) {
+        let seq_bytes = slot.seq.to_le_bytes();
+        let (slot_key, bump) = Pubkey::find_program_address(
+            &[b"slot", experience_key.as_ref(), seq_bytes.as_ref()],
+            ctx.program_id
+        );
+        require_keys_eq!(slot_info.key(), slot_key, ErrorCode::InvalidSlotAccount);
+        require!(slot_info.data_is_empty(), ErrorCode::InvalidSlotAccount);
+
+        slot_registry::create_pda(
+            slot_info,
+            &ctx.accounts.organiser.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.program_id,
+            space,
+            &[b"slot", experience_key.as_ref(), seq_bytes.as_ref(), &[bump]]
+        )?;
+        slot.try_serialize(&mut &mut slot_info.try_borrow_mut_data()?[..])?;
+
+        let page_index = (SlotRegistryPage::page_of(slot.seq) - SlotRegistryPage::page_of(first_seq)) as usize;
+        slot_registry::register(
+            &page_infos[page_index],
+            &ctx.accounts.organiser.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &experience_key,
+            ctx.program_id,
+            slot.seq,
+            RegisteredSlot { start_time: slot.start_time, end_time: slot.end_time, removed: false }
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ program::invoke, system_instruction };
+use anchor_spl::token::{ transfer_checked, Mint, Token, TokenAccount, TransferChecked };
+
+use crate::{ ErrorCode, Experience };
+
+// highest price a ticket may be resold at, `max_markup_percent` over the slot price
+pub fn max_resale_price(slot_price: u64, max_markup_percent: u16) -> Result<u64> {
+    let max_price = ((slot_price as u128) * (100 + max_markup_percent as u128)) / 100;
+    u64::try_from(max_price).map_err(|_| error!(ErrorCode::AmountOverflow))
+}
+
+// splits a resale price into (seller proceeds, organiser royalty)
+pub fn resale_split(price: u64, royalty_percent: u8) -> Result<(u64, u64)> {
+    require!(royalty_percent <= 100, ErrorCode::InvalidResaleTerms);
+
+    let royalty = ((price as u128) * (royalty_percent as u128)) / 100;
+    let royalty = u64::try_from(royalty).map_err(|_| ErrorCode::AmountOverflow)?;
+    let proceeds = price.checked_sub(royalty).ok_or(ErrorCode::AmountOverflow)?;
+
+    Ok((proceeds, royalty))
+}
+
+// the buyer's side of a resale, paid directly to the seller and organiser rather than through the vault
+pub struct ResalePayment<'a, 'info> {
+    pub buyer: &'a Signer<'info>,
+    pub system_program: &'a Program<'info, System>,
+    pub token: Option<BuyerTokens<'a, 'info>>,
+}
+
+pub struct BuyerTokens<'a, 'info> {
+    pub token_program: &'a Program<'info, Token>,
+    pub mint: &'a Account<'info, Mint>,
+    pub buyer_account: &'a Account<'info, TokenAccount>,
+}
+
+impl<'a, 'info> ResalePayment<'a, 'info> {
+    pub fn load(
+        experience: &Experience,
+        buyer: &'a Signer<'info>,
+        system_program: &'a Program<'info, System>,
+        token_program: Option<&'a Program<'info, Token>>,
+        mint: Option<&'a Account<'info, Mint>>,
+        buyer_account: Option<&'a Account<'info, TokenAccount>>
+    ) -> Result<Self> {
+        let Some(payment_mint) = experience.payment_mint else {
+            return Ok(Self { buyer, system_program, token: None });
+        };
+        let (Some(token_program), Some(mint), Some(buyer_account)) = (
+            token_program,
+            mint,
+            buyer_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+
+        require_keys_eq!(mint.key(), payment_mint, ErrorCode::InvalidPaymentMint);
+
+        Ok(Self {
+            buyer,
+            system_program,
+            token: Some(BuyerTokens { token_program, mint, buyer_account }),
+        })
+    }
+
+    // pays `amount` to `to`, or to its token account for token-priced experiences
+    pub fn pay(
+        &self,
+        to: &AccountInfo<'info>,
+        to_tokens: Option<&Account<'info, TokenAccount>>,
+        amount: u64
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+
+        let Some(token) = &self.token else {
+            require!(self.buyer.lamports() >= amount, ErrorCode::InsufficientFunds);
+            invoke(
+                &system_instruction::transfer(&self.buyer.key(), &to.key(), amount),
+                &[self.buyer.to_account_info(), to.clone(), self.system_program.to_account_info()]
+            )?;
+            return Ok(());
+        };
+
+        let to_tokens = to_tokens.ok_or(ErrorCode::MissingPaymentAccounts)?;
+        require_keys_eq!(to_tokens.mint, token.mint.key(), ErrorCode::InvalidPaymentAccount);
+        require_keys_eq!(to_tokens.owner, to.key(), ErrorCode::InvalidPaymentAccount);
+
+        let cpi_ctx = CpiContext::new(token.token_program.to_account_info(), TransferChecked {
+            from: token.buyer_account.to_account_info(),
+            mint: token.mint.to_account_info(),
+            to: to_tokens.to_account_info(),
+            authority: self.buyer.to_account_info(),
+        });
+        transfer_checked(cpi_ctx, amount, token.mint.decimals)
+    }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{ allocate, assign, transfer, Allocate, Assign, Transfer };
+
+use crate::{ ErrorCode, RegisteredSlot, SlotRegistryPage };
+
+// appends slot `seq` to its registry page, creating the page when the previous one has filled up
+pub fn register<'info>(
+    page_info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    experience: &Pubkey,
+    program_id: &Pubkey,
+    seq: u64,
+    entry: RegisteredSlot
+) -> Result<()> {
+    let page = SlotRegistryPage::page_of(seq);
+    let page_bytes = page.to_le_bytes();
+    let (page_key, bump) = Pubkey::find_program_address(
+        &[b"slot_registry", experience.as_ref(), page_bytes.as_ref()],
+        program_id
+    );
+    require_keys_eq!(page_info.key(), page_key, ErrorCode::InvalidRegistryPage);
+
+    if page_info.data_is_empty() {
+        create_pda(
+            page_info,
+            payer,
+            system_program,
+            program_id,
+            8 + SlotRegistryPage::LEN,
+            &[b"slot_registry", experience.as_ref(), page_bytes.as_ref(), &[bump]]
+        )?;
+
+        let registry_page = SlotRegistryPage {
+            experience: *experience,
+            page,
+            slots: Vec::new(),
+        };
+        registry_page.try_serialize(&mut &mut page_info.try_borrow_mut_data()?[..])?;
+    }
+
+    let mut registry_page = SlotRegistryPage::try_deserialize(&mut &page_info.try_borrow_data()?[..])?;
+    require!(
+        registry_page.slots.len() == SlotRegistryPage::index_of(seq),
+        ErrorCode::InvalidRegistryPage
+    );
+    registry_page.slots.push(entry);
+    registry_page.try_serialize(&mut &mut page_info.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+// creates a program owned PDA the way Anchor's `init` does, topping up whatever lamports the address
+// already holds instead of failing, so a transfer to a predictable address can't block its creation
+pub fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    program_id: &Pubkey,
+    space: usize,
+    seeds: &[&[u8]]
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(space);
+    let current = account.lamports();
+    if required > current {
+        transfer(
+            CpiContext::new(system_program.clone(), Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            }),
+            required - current
+        )?;
+    }
+    allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate { account_to_allocate: account.clone() },
+            &[seeds]
+        ),
+        space as u64
+    )?;
+    assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign { account_to_assign: account.clone() },
+            &[seeds]
+        ),
+        program_id
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::types::Creator;
+
+use crate::ErrorCode;
+
+// a co-host credited on the booking NFTs next to the organiser
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoCreator {
+    pub address: Pubkey,
+    pub share: u8, // percent of the royalties
+}
+
+impl CoCreator {
+    pub const LEN: usize =
+        32 + // address
+        1; // share (u8)
+}
+
+// royalty and creator split written into every booking NFT of an experience
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TicketRoyalties {
+    pub seller_fee_basis_points: u16,
+    pub co_creators: Vec<CoCreator>,
+}
+
+impl TicketRoyalties {
+    // Metaplex allows five creators, two are taken by the program authority and the organiser
+    pub const MAX_CO_CREATORS: usize = mpl_token_metadata::MAX_CREATOR_LIMIT - 2;
+    pub const MAX_BASIS_POINTS: u16 = 10_000;
+    pub const LEN: usize =
+        2 + // seller_fee_basis_points
+        4 +
+        Self::MAX_CO_CREATORS * CoCreator::LEN; // co_creators
+
+    pub fn validate(&self, organiser: &Pubkey, program_authority: &Pubkey) -> Result<()> {
+        require!(
+            self.seller_fee_basis_points <= Self::MAX_BASIS_POINTS,
+            ErrorCode::InvalidTicketRoyalties
+        );
+        require!(self.co_creators.len() <= Self::MAX_CO_CREATORS, ErrorCode::InvalidTicketRoyalties);
+
+        let mut total_share: u16 = 0;
+        for (i, co_creator) in self.co_creators.iter().enumerate() {
+            require!(
+                co_creator.address != *organiser && co_creator.address != *program_authority,
+                ErrorCode::InvalidTicketRoyalties
+            );
+            require!(
+                self.co_creators[..i].iter().all(|other| other.address != co_creator.address),
+                ErrorCode::InvalidTicketRoyalties
+            );
+            total_share += co_creator.share as u16;
+        }
+        require!(total_share <= 100, ErrorCode::InvalidTicketRoyalties);
+
+        Ok(())
+    }
+
+    // creators array for the NFT metadata, the organiser gets whatever share the co-hosts leave.
+    // Only the program authority PDA signs the mint, so it is verified up front and vouches that the
+    // ticket came from this program; it takes no share of the royalties. The organiser and co-hosts
+    // sign their own entries later through `verify_ticket_creator`.
+    pub fn creators(&self, organiser: Pubkey, program_authority: Pubkey) -> Vec<Creator> {
+        let co_host_share: u8 = self.co_creators.iter().map(|co_creator| co_creator.share).sum();
+
+        let mut creators = vec![
+            Creator { address: program_authority, verified: true, share: 0 },
+            Creator { address: organiser, verified: false, share: 100 - co_host_share }
+        ];
+        creators.extend(
+            self.co_creators.iter().map(|co_creator| Creator {
+                address: co_creator.address,
+                verified: false,
+                share: co_creator.share,
+            })
+        );
+        creators
+    }
+}
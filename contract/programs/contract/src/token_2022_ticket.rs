@@ -0,0 +1,382 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token_2022::{
+    self,
+    spl_token_2022::{
+        extension::{ BaseStateWithExtensions, ExtensionType, StateWithExtensions },
+        instruction::AuthorityType,
+        state::Mint,
+    },
+    Burn,
+    CloseAccount,
+    InitializeMint2,
+    MintTo,
+    SetAuthority,
+};
+use anchor_spl::token_2022_extensions::{
+    metadata_pointer_initialize,
+    mint_close_authority_initialize,
+    non_transferable_mint_initialize,
+    spl_pod::optional_keys::OptionalNonZeroPubkey,
+    spl_token_metadata_interface::state::{ Field, TokenMetadata },
+    token_metadata_initialize,
+    token_metadata_update_field,
+    MetadataPointerInitialize,
+    MintCloseAuthorityInitialize,
+    NonTransferableMintInitialize,
+    TokenMetadataInitialize,
+    TokenMetadataUpdateField,
+};
+
+use crate::build_metadata::ticket_data;
+use crate::held_ticket::HeldTicket;
+use crate::new_ticket::NewTicket;
+use crate::{ nft_metadata, ErrorCode, TimeSlotAccount };
+
+// extensions initialised on every Token-2022 ticket mint, the metadata itself is added after
+const MINT_EXTENSIONS: [ExtensionType; 2] = [
+    ExtensionType::MetadataPointer,
+    ExtensionType::MintCloseAuthority,
+];
+
+// additional metadata stored on the ticket as key/value pairs
+fn ticket_fields(
+    experience: Pubkey,
+    reservation: Pubkey,
+    start_time: i64,
+    end_time: i64,
+    seats: u16
+) -> Vec<(String, String)> {
+    vec![
+        ("experience".to_string(), experience.to_string()),
+        ("reservation".to_string(), reservation.to_string()),
+        ("start_time".to_string(), start_time.to_string()),
+        ("end_time".to_string(), end_time.to_string()),
+        ("seats".to_string(), seats.to_string())
+    ]
+}
+
+// tops the mint up so it stays rent exempt once its data grows to `new_len`
+fn fund_mint<'info>(
+    mint: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(new_len);
+    let current = mint.lamports();
+    if required > current {
+        invoke(
+            &system_instruction::transfer(payer.key, mint.key, required - current),
+            &[payer.clone(), mint.clone(), system_program.clone()]
+        )?;
+    }
+    Ok(())
+}
+
+// mints a Token-2022 booking ticket carrying its own metadata
+pub fn mint_ticket(ticket: &NewTicket) -> Result<()> {
+    let (Some(ticket_token_program), Some(mint), Some(owner_nft_account), Some(associated_token_program)) = (
+        &ticket.ticket_token_program,
+        &ticket.mint,
+        &ticket.owner_nft_account,
+        &ticket.associated_token_program,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    let token_program = ticket_token_program.clone();
+    let mint = mint.clone();
+    let mint_authority = ticket.mint_authority.clone();
+    let authority_seeds: &[&[u8]] = &[b"mint_authority", &[ticket.mint_authority_bump]];
+    let signer_seeds = &[authority_seeds];
+
+    let metadata_uri = nft_metadata::create_metadata_uri(
+        ticket.experience,
+        &ticket.reservation,
+        &mint.key(),
+        ticket.slot.start_time,
+        ticket.slot.end_time,
+        ticket.seats
+    )?;
+    let data = ticket_data(ticket.experience, mint_authority.key(), metadata_uri);
+    let fields = ticket_fields(
+        ticket.experience.key(),
+        ticket.reservation,
+        ticket.slot.start_time,
+        ticket.slot.end_time,
+        ticket.seats
+    );
+    let metadata = TokenMetadata {
+        update_authority: OptionalNonZeroPubkey::try_from(Some(mint_authority.key()))?,
+        mint: mint.key(),
+        name: data.name.clone(),
+        symbol: data.symbol.clone(),
+        uri: data.uri.clone(),
+        additional_metadata: fields.clone(),
+    };
+
+    // soulbound tickets can still be burned and closed, just never moved
+    let transferable = ticket.experience.transferable;
+    let mut extensions = MINT_EXTENSIONS.to_vec();
+    if !transferable {
+        extensions.push(ExtensionType::NonTransferable);
+    }
+
+    // Mint account creation, funded up front for the metadata written below
+    let mint_len = ExtensionType::try_calculate_account_len::<Mint>(&extensions)?;
+    let mint_rent = Rent::get()?.minimum_balance(mint_len + metadata.tlv_size_of()?);
+    invoke(
+        &system_instruction::create_account(
+            &ticket.payer.key(),
+            &mint.key(),
+            mint_rent,
+            mint_len as u64,
+            &token_program.key()
+        ),
+        &[ticket.payer.clone(), mint.clone(), ticket.system_program.clone()]
+    )?;
+
+    // the mint points at itself for metadata, the program authority PDA can close it on cancel
+    metadata_pointer_initialize(
+        CpiContext::new(token_program.clone(), MetadataPointerInitialize {
+            token_program_id: token_program.clone(),
+            mint: mint.clone(),
+        }),
+        Some(mint_authority.key()),
+        Some(mint.key())
+    )?;
+    mint_close_authority_initialize(
+        CpiContext::new(token_program.clone(), MintCloseAuthorityInitialize {
+            token_program_id: token_program.clone(),
+            mint: mint.clone(),
+        }),
+        Some(&mint_authority.key())
+    )?;
+    if !transferable {
+        non_transferable_mint_initialize(
+            CpiContext::new(token_program.clone(), NonTransferableMintInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            })
+        )?;
+    }
+    token_2022::initialize_mint2(
+        CpiContext::new(token_program.clone(), InitializeMint2 { mint: mint.clone() }),
+        0,
+        &mint_authority.key(),
+        Some(&mint_authority.key())
+    )?;
+
+    token_metadata_initialize(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            TokenMetadataInitialize {
+                program_id: token_program.clone(),
+                metadata: mint.clone(),
+                update_authority: mint_authority.clone(),
+                mint_authority: mint_authority.clone(),
+                mint: mint.clone(),
+            },
+            signer_seeds
+        ),
+        data.name,
+        data.symbol,
+        data.uri
+    )?;
+    for (key, value) in fields {
+        token_metadata_update_field(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TokenMetadataUpdateField {
+                    program_id: token_program.clone(),
+                    metadata: mint.clone(),
+                    update_authority: mint_authority.clone(),
+                },
+                signer_seeds
+            ),
+            Field::Key(key),
+            value
+        )?;
+    }
+
+    // Create ATA (associated token account)
+    let cpi_ctx = CpiContext::new(
+        associated_token_program.clone(),
+        anchor_spl::associated_token::Create {
+            payer: ticket.payer.clone(),
+            associated_token: owner_nft_account.clone(),
+            authority: ticket.owner.clone(),
+            mint: mint.clone(),
+            system_program: ticket.system_program.clone(),
+            token_program: token_program.clone(),
+        }
+    );
+    anchor_spl::associated_token::create(cpi_ctx)?;
+
+    // Mint 1 token, then drop the mint authority so the supply is fixed
+    token_2022::mint_to(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            MintTo {
+                mint: mint.clone(),
+                to: owner_nft_account.clone(),
+                authority: mint_authority.clone(),
+            },
+            signer_seeds
+        ),
+        1
+    )?;
+    token_2022::set_authority(
+        CpiContext::new_with_signer(
+            token_program,
+            SetAuthority {
+                current_authority: mint_authority,
+                account_or_mint: mint,
+            },
+            signer_seeds
+        ),
+        AuthorityType::MintTokens,
+        None
+    )?;
+
+    Ok(())
+}
+
+// burns the ticket and closes its token account and mint, rent goes back to the holder
+pub fn burn_ticket(ticket: &HeldTicket) -> Result<()> {
+    let (Some(holder_nft_account), Some(token_program)) = (
+        &ticket.holder_nft_account,
+        &ticket.ticket_token_program,
+    ) else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    token_2022::burn(
+        CpiContext::new(token_program.clone(), Burn {
+            mint: ticket.mint.clone(),
+            from: holder_nft_account.clone(),
+            authority: ticket.holder.clone(),
+        }),
+        1
+    )?;
+    token_2022::close_account(
+        CpiContext::new(token_program.clone(), CloseAccount {
+            account: holder_nft_account.clone(),
+            destination: ticket.holder.clone(),
+            authority: ticket.holder.clone(),
+        })
+    )?;
+    token_2022::close_account(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            CloseAccount {
+                account: ticket.mint.clone(),
+                destination: ticket.holder.clone(),
+                authority: ticket.mint_authority.clone(),
+            },
+            &[&[b"mint_authority", &[ticket.mint_authority_bump]]]
+        )
+    )?;
+
+    Ok(())
+}
+
+// renames a ticket that can't be burnt and flags it void so it no longer passes as a booking
+pub fn void_ticket(ticket: &HeldTicket) -> Result<()> {
+    let Some(token_program) = &ticket.ticket_token_program else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+
+    update_fields(
+        token_program,
+        &ticket.mint,
+        &ticket.mint_authority,
+        ticket.mint_authority_bump,
+        &ticket.payer,
+        &ticket.system_program,
+        vec![
+            (Field::Name, nft_metadata::void_ticket_name(ticket.experience)),
+            (Field::Key("status".to_string()), "void".to_string())
+        ]
+    )
+}
+
+// rewrites the ticket URI and fields for `reservation`'s slot and party size, after a reschedule or
+// a partial cancel
+pub fn update_ticket(
+    ticket: &HeldTicket,
+    reservation: &Pubkey,
+    slot: &TimeSlotAccount,
+    seats: u16
+) -> Result<()> {
+    let Some(token_program) = &ticket.ticket_token_program else {
+        return err!(ErrorCode::MissingTicketAccounts);
+    };
+    let metadata_uri = nft_metadata::create_metadata_uri(
+        ticket.experience,
+        reservation,
+        &ticket.mint.key(),
+        slot.start_time,
+        slot.end_time,
+        seats
+    )?;
+    let mut updates = vec![(Field::Uri, metadata_uri)];
+    updates.extend(
+        ticket_fields(ticket.experience.key(), *reservation, slot.start_time, slot.end_time, seats)
+            .into_iter()
+            .map(|(key, value)| (Field::Key(key), value))
+    );
+
+    update_fields(
+        token_program,
+        &ticket.mint,
+        &ticket.mint_authority,
+        ticket.mint_authority_bump,
+        &ticket.payer,
+        &ticket.system_program,
+        updates
+    )
+}
+
+// writes `updates` into the mint's metadata as the program authority, `payer` funds the growth
+fn update_fields<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    mint_authority: &AccountInfo<'info>,
+    mint_authority_bump: u8,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    updates: Vec<(Field, String)>
+) -> Result<()> {
+    // work out how far the metadata grows so the mint can be funded first
+    let new_len = {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<Mint>::unpack(&data)?;
+        let mut metadata = state.get_variable_len_extension::<TokenMetadata>()?;
+        let old_size = metadata.tlv_size_of()?;
+        for (field, value) in &updates {
+            metadata.update(field.clone(), value.clone());
+        }
+        data.len() - old_size + metadata.tlv_size_of()?
+    };
+    fund_mint(mint, payer, system_program, new_len)?;
+
+    for (field, value) in updates {
+        token_metadata_update_field(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TokenMetadataUpdateField {
+                    program_id: token_program.clone(),
+                    metadata: mint.clone(),
+                    update_authority: mint_authority.clone(),
+                },
+                &[&[b"mint_authority", &[mint_authority_bump]]]
+            ),
+            field,
+            value
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ program::invoke, system_instruction };
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{ transfer_checked, Mint, Token, TokenAccount, TransferChecked },
+};
+
+use crate::{ ErrorCode, Experience, Vault };
+
+// accounts holding an experience's escrow when it is priced in an SPL token
+pub struct TokenEscrow<'a, 'info> {
+    pub token_program: &'a Program<'info, Token>,
+    pub mint: &'a Account<'info, Mint>,
+    pub vault_account: &'a Account<'info, TokenAccount>,
+}
+
+impl<'a, 'info> TokenEscrow<'a, 'info> {
+    // returns None for experiences priced in lamports
+    pub fn load(
+        experience: &Experience,
+        vault: &Account<'info, Vault>,
+        token_program: Option<&'a Program<'info, Token>>,
+        mint: Option<&'a Account<'info, Mint>>,
+        vault_account: Option<&'a Account<'info, TokenAccount>>
+    ) -> Result<Option<Self>> {
+        let Some(payment_mint) = experience.payment_mint else {
+            return Ok(None);
+        };
+        let (Some(token_program), Some(mint), Some(vault_account)) = (
+            token_program,
+            mint,
+            vault_account,
+        ) else {
+            return err!(ErrorCode::MissingPaymentAccounts);
+        };
+
+        require_keys_eq!(mint.key(), payment_mint, ErrorCode::InvalidPaymentMint);
+        require_keys_eq!(
+            vault_account.key(),
+            get_associated_token_address(&vault.key(), &payment_mint),
+            ErrorCode::InvalidPaymentAccount
+        );
+
+        Ok(Some(Self { token_program, mint, vault_account }))
+    }
+}
+
+// moves a payment from the payer into the vault, in lamports or in the experience's token
+pub fn deposit<'info>(
+    escrow: Option<&TokenEscrow<'_, 'info>>,
+    payer: &Signer<'info>,
+    payer_tokens: Option<&Account<'info, TokenAccount>>,
+    vault: &Account<'info, Vault>,
+    system_program: &Program<'info, System>,
+    amount: u64
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let Some(escrow) = escrow else {
+        require!(payer.lamports() >= amount, ErrorCode::InsufficientFunds);
+        invoke(
+            &system_instruction::transfer(&payer.key(), &vault.key(), amount),
+            &[payer.to_account_info(), vault.to_account_info(), system_program.to_account_info()]
+        )?;
+        return Ok(());
+    };
+
+    let payer_tokens = payer_tokens.ok_or(ErrorCode::MissingPaymentAccounts)?;
+    let cpi_ctx = CpiContext::new(escrow.token_program.to_account_info(), TransferChecked {
+        from: payer_tokens.to_account_info(),
+        mint: escrow.mint.to_account_info(),
+        to: escrow.vault_account.to_account_info(),
+        authority: payer.to_account_info(),
+    });
+    transfer_checked(cpi_ctx, amount, escrow.mint.decimals)
+}
+
+// pays escrowed funds out of the vault to `to`, or to its token account for token-priced experiences
+pub fn pay_out<'info>(
+    escrow: Option<&TokenEscrow<'_, 'info>>,
+    vault: &Account<'info, Vault>,
+    to: &AccountInfo<'info>,
+    to_tokens: Option<&Account<'info, TokenAccount>>,
+    amount: u64
+) -> Result<()> {
+    let Some(escrow) = escrow else {
+        return release(&vault.to_account_info(), to, amount);
+    };
+
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let to_tokens = to_tokens.ok_or(ErrorCode::MissingPaymentAccounts)?;
+    require_keys_eq!(to_tokens.mint, escrow.mint.key(), ErrorCode::InvalidPaymentAccount);
+    require_keys_eq!(to_tokens.owner, to.key(), ErrorCode::InvalidPaymentAccount);
+
+    let vault_seeds: &[&[u8]] = &[b"vault", vault.experience.as_ref(), &[vault.bump]];
+    let signer_seeds = &[vault_seeds];
+    let cpi_ctx = CpiContext::new_with_signer(
+        escrow.token_program.to_account_info(),
+        TransferChecked {
+            from: escrow.vault_account.to_account_info(),
+            mint: escrow.mint.to_account_info(),
+            to: to_tokens.to_account_info(),
+            authority: vault.to_account_info(),
+        },
+        signer_seeds
+    );
+    transfer_checked(cpi_ctx, amount, escrow.mint.decimals)
+}
+
+// moves escrowed lamports out of the program-owned vault
+pub fn release<'info>(
+    vault: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    vault.sub_lamports(amount)?;
+    to.add_lamports(amount)?;
+
+    Ok(())
+}
+
+// splits an escrowed amount into (refund, fee) for a percentage cancellation fee
+pub fn cancellation_split(amount: u64, fee_percent: u64) -> Result<(u64, u64)> {
+    require!(fee_percent <= 100, ErrorCode::InvalidCancellationFee);
+
+    let fee = ((amount as u128) * (fee_percent as u128)) / 100;
+    let fee = u64::try_from(fee).map_err(|_| ErrorCode::AmountOverflow)?;
+    let refund = amount.checked_sub(fee).ok_or(ErrorCode::AmountOverflow)?;
+
+    Ok((refund, fee))
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::vault::{ self, TokenEscrow };
+use crate::{ ErrorCode, TimeSlotAccount, Vault, WaitlistEntry };
+
+// refunds and closes the waitlist entries of a cancelled slot, starting at the head of the queue.
+// Each entry in `accounts` is followed by its user and, for token priced experiences, the user's
+// payment account
+pub fn refund_entries<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    slot: &mut Account<'info, TimeSlotAccount>,
+    escrow: Option<&TokenEscrow<'_, 'info>>,
+    vault: &Account<'info, Vault>,
+    program_id: &Pubkey
+) -> Result<()> {
+    let stride = if escrow.is_some() { 3 } else { 2 };
+    let entries = accounts.chunks_exact(stride);
+    require!(entries.remainder().is_empty(), ErrorCode::InvalidWaitlistEntry);
+
+    for entry_accounts in entries {
+        let (entry_key, _) = Pubkey::find_program_address(
+            &[b"waitlist", slot.key().as_ref(), slot.waitlist_head.to_le_bytes().as_ref()],
+            program_id
+        );
+        require_keys_eq!(entry_accounts[0].key(), entry_key, ErrorCode::InvalidWaitlistEntry);
+
+        let entry = Account::<WaitlistEntry>::try_from(&entry_accounts[0])?;
+        let user = &entry_accounts[1];
+        require_keys_eq!(user.key(), entry.user, ErrorCode::InvalidWaitlistEntry);
+        let user_tokens = entry_accounts.get(2).map(Account::<TokenAccount>::try_from).transpose()?;
+
+        vault::pay_out(escrow, vault, user, user_tokens.as_ref(), entry.deposit)?;
+        slot.pop_waitlist();
+        entry.close(user.clone())?;
+    }
+
+    Ok(())
+}
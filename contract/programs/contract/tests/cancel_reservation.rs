@@ -0,0 +1,617 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::Once;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{ self, SyscallStubs };
+use anchor_lang::solana_program::{ entrypoint::SUCCESS, sysvar };
+use anchor_lang::{ InstructionData, ToAccountMetas };
+use anchor_spl::token::spl_token;
+use contract::cancellation_policy::{ CancellationPolicy, CancellationTier };
+use contract::ticket_royalties::TicketRoyalties;
+use contract::{
+    vault,
+    ErrorCode,
+    Experience,
+    RegisteredSlot,
+    Reservation,
+    SlotRegistryPage,
+    SlotSchedule,
+    TicketMode,
+    TimeSlotAccount,
+    Vault,
+};
+
+const NOW: i64 = 1_700_000_000;
+const HOUR: i64 = 60 * 60;
+const SLOT_PRICE: u64 = 1_000_000_000;
+
+thread_local! {
+    // program ids and PDA signers of every CPI made by the instruction under test
+    static CPIS: RefCell<Vec<(Pubkey, Vec<Pubkey>)>> = const { RefCell::new(Vec::new()) };
+}
+
+// serves the clock and rent sysvars and records CPIs instead of running them
+struct TestStubs;
+
+impl SyscallStubs for TestStubs {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock { unix_timestamp: NOW, ..Clock::default() };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]]
+    ) -> std::result::Result<(), ProgramError> {
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &contract::ID).unwrap())
+            .collect();
+        CPIS.with(|cpis| cpis.borrow_mut().push((instruction.program_id, signers)));
+        Ok(())
+    }
+}
+
+struct TestAccount {
+    owner: Pubkey,
+    lamports: u64,
+    // laid out like the runtime's input buffer, the length first so `realloc` can rewrite it
+    data: Vec<u8>,
+    executable: bool,
+}
+
+impl TestAccount {
+    fn new(owner: Pubkey, lamports: u64, data: Vec<u8>, executable: bool) -> Self {
+        let mut buffer = (data.len() as u64).to_le_bytes().to_vec();
+        buffer.extend(data);
+        Self { owner, lamports, data: buffer, executable }
+    }
+
+    fn data(&self) -> &[u8] {
+        let len = u64::from_le_bytes(self.data[..8].try_into().unwrap()) as usize;
+        &self.data[8..8 + len]
+    }
+}
+
+// accounts of one booking, refunded through the real `cancel_reservation` and `organiser_cancel_slot` entrypoints
+struct Booking {
+    accounts: HashMap<Pubkey, TestAccount>,
+    user: Pubkey,
+    organiser: Pubkey,
+    experience: Pubkey,
+    vault: Pubkey,
+    reservation: Pubkey,
+    slot: Pubkey,
+    mint: Pubkey,
+    user_nft_account: Pubkey,
+    start_time: i64,
+}
+
+fn vault_rent() -> u64 {
+    Rent::default().minimum_balance(8 + Vault::LEN)
+}
+
+fn policy() -> CancellationPolicy {
+    CancellationPolicy {
+        tiers: vec![
+            CancellationTier { min_notice_seconds: 48 * HOUR, refund_percent: 100 },
+            CancellationTier { min_notice_seconds: 24 * HOUR, refund_percent: 90 }
+        ],
+    }
+}
+
+impl Booking {
+    // a reservation of `seats` seats for a slot starting `notice_seconds` from now
+    fn new(seats: u16, notice_seconds: i64, transferable: bool) -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            program_stubs::set_syscall_stubs(Box::new(TestStubs));
+        });
+        CPIS.with(|cpis| cpis.borrow_mut().clear());
+
+        let user = Pubkey::new_unique();
+        let organiser = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let start_time = NOW + notice_seconds;
+        let experience = Pubkey::new_unique();
+        let (vault, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", experience.as_ref()],
+            &contract::ID
+        );
+        let (slot, _) = Pubkey::find_program_address(
+            &[b"slot", experience.as_ref(), 0u64.to_le_bytes().as_ref()],
+            &contract::ID
+        );
+        let (reservation, _) = Pubkey::find_program_address(
+            &[b"reservation", experience.as_ref(), start_time.to_le_bytes().as_ref(), user.as_ref()],
+            &contract::ID
+        );
+        let amount_paid = SLOT_PRICE * (seats as u64);
+
+        let mut booking = Self {
+            accounts: HashMap::new(),
+            user,
+            organiser,
+            experience,
+            vault,
+            reservation,
+            slot,
+            mint,
+            user_nft_account: Pubkey::new_unique(),
+            start_time,
+        };
+
+        booking.add_program(contract::ID);
+        booking.add_program(spl_token::ID);
+        booking.add_program(System::id());
+        booking.add_program(mpl_token_metadata::ID);
+        booking.add(user, System::id(), 0, Vec::new());
+        booking.add(organiser, System::id(), 0, Vec::new());
+        booking.add(mint, spl_token::ID, 0, Vec::new());
+        booking.add(sysvar::instructions::ID, sysvar::ID, 0, Vec::new());
+        for key in [mint_authority(), master_edition(&mint)] {
+            booking.add(key, System::id(), 0, Vec::new());
+        }
+        booking.add(metadata(&mint), mpl_token_metadata::ID, 0, ticket_metadata(&mint));
+
+        booking.add_state(experience, &(Experience {
+            organiser,
+            title: "Harbour Walk".to_string(),
+            title_seed: "Harbour Walk".to_string(),
+            description: String::new(),
+            location: None,
+            price_lamports: SLOT_PRICE,
+            cancellation_policy: policy(),
+            metadata_uri_template: String::new(),
+            time_slot_count: 1,
+            next_slot_seq: 1,
+            check_in_delegate: None,
+            payment_mint: None,
+            ticket_mode: TicketMode::Metaplex,
+            merkle_tree: None,
+            collection_mint: None,
+            transferable,
+            max_resale_markup_percent: 0,
+            resale_royalty_percent: 0,
+            ticket_royalties: TicketRoyalties::default(),
+        }));
+        booking.add_state(vault, &(Vault { experience, bump: vault_bump }));
+        booking.accounts.get_mut(&vault).unwrap().lamports = vault_rent() + amount_paid;
+        booking.add_state(slot, &(TimeSlotAccount {
+            experience,
+            seq: 0,
+            start_time,
+            end_time: start_time + HOUR,
+            price: SLOT_PRICE,
+            capacity: 10,
+            booked_count: seats,
+            is_cancelled: false,
+            held_seats: 0,
+            waitlist_head: 0,
+            waitlist_tail: 0,
+            prev_seq: None,
+            next_seq: None,
+        }));
+        booking.add_state(schedule(&experience), &(SlotSchedule {
+            experience,
+            buffer_seconds: 0,
+            first_seq: Some(0),
+        }));
+        booking.add_state(registry_page(&experience), &(SlotRegistryPage {
+            experience,
+            page: 0,
+            slots: vec![RegisteredSlot { start_time, end_time: start_time + HOUR, removed: false }],
+        }));
+        booking.add_state(reservation, &(Reservation {
+            experience_id: experience,
+            user,
+            booker: user,
+            time_slot: start_time,
+            nft_mint: mint,
+            start_time,
+            end_time: start_time + HOUR,
+            is_active: true,
+            seats,
+            amount_paid,
+            payout_claimed: false,
+            cancelled_by_organiser: false,
+            checked_in: false,
+            ticket_nonce: 0,
+            ticket_data_hash: [0; 32],
+            ticket_creator_hash: [0; 32],
+        }));
+        booking.give_ticket_to(user);
+
+        booking
+    }
+
+    fn add(&mut self, key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>) {
+        self.accounts.insert(key, TestAccount::new(owner, lamports, data, false));
+    }
+
+    fn add_program(&mut self, key: Pubkey) {
+        self.accounts.insert(key, TestAccount::new(Pubkey::default(), 1, Vec::new(), true));
+    }
+
+    fn add_state<T: AccountSerialize>(&mut self, key: Pubkey, state: &T) {
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        let lamports = Rent::default().minimum_balance(data.len());
+        self.add(key, contract::ID, lamports, data);
+    }
+
+    // moves the booking NFT to `holder`
+    fn give_ticket_to(&mut self, holder: Pubkey) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(
+            spl_token::state::Account {
+                mint: self.mint,
+                owner: holder,
+                amount: 1,
+                state: spl_token::state::AccountState::Initialized,
+                ..Default::default()
+            },
+            &mut data
+        ).unwrap();
+        self.add(self.user_nft_account, spl_token::ID, 0, data);
+    }
+
+    // someone else's booking keeps the slot open once this one is cancelled
+    fn book_another_seat(&mut self) {
+        let slot = TimeSlotAccount { booked_count: self.slot().booked_count + 1, ..self.slot() };
+        self.add_state(self.slot, &slot);
+    }
+
+    // the organiser has cancelled the slot but not yet refunded this booking
+    fn cancel_slot(&mut self) {
+        let slot = TimeSlotAccount { is_cancelled: true, ..self.slot() };
+        self.add_state(self.slot, &slot);
+    }
+
+    fn check_in(&mut self) {
+        let reservation = Reservation { checked_in: true, ..self.reservation() };
+        self.add_state(self.reservation, &reservation);
+    }
+
+    fn lamports(&self, key: &Pubkey) -> u64 {
+        self.accounts[key].lamports
+    }
+
+    fn is_closed(&self, key: &Pubkey) -> bool {
+        let account = &self.accounts[key];
+        account.lamports == 0 && account.data().is_empty() && account.owner == System::id()
+    }
+
+    fn reservation(&self) -> Reservation {
+        Reservation::try_deserialize(&mut self.accounts[&self.reservation].data()).unwrap()
+    }
+
+    fn slot(&self) -> TimeSlotAccount {
+        TimeSlotAccount::try_deserialize(&mut self.accounts[&self.slot].data()).unwrap()
+    }
+
+    // runs `cancel_reservation` signed by `signer`
+    fn cancel(&mut self, signer: Pubkey, seats: Option<u16>) -> std::result::Result<(), ProgramError> {
+        let metas = (contract::accounts::CancelReservation {
+            user: signer,
+            experience: self.experience,
+            organiser: self.organiser,
+            vault: self.vault,
+            reservation: self.reservation,
+            slot: self.slot,
+            waitlist_entry: None,
+            mint: self.mint,
+            user_nft_account: Some(self.user_nft_account),
+            mint_authority: mint_authority(),
+            payment_mint: None,
+            vault_payment_account: None,
+            user_payment_account: None,
+            organiser_payment_account: None,
+            metadata: Some(metadata(&self.mint)),
+            master_edition: Some(master_edition(&self.mint)),
+            collection_metadata: None,
+            token_program: spl_token::ID,
+            ticket_token_program: None,
+            system_program: System::id(),
+            metadata_program: Some(mpl_token_metadata::ID),
+            sysvar_instructions: Some(sysvar::instructions::ID),
+            merkle_tree: None,
+            tree_config: None,
+            bubblegum_program: None,
+            log_wrapper: None,
+            compression_program: None,
+            leaf_delegate: None,
+        }).to_account_metas(None);
+        let data = (contract::instruction::CancelReservation {
+            _start_time: self.start_time,
+            seats,
+            ticket_root: None,
+        }).data();
+
+        if !self.accounts.contains_key(&signer) {
+            self.add(signer, System::id(), 0, Vec::new());
+        }
+        self.run(&metas, &data)
+    }
+
+    // runs `organiser_cancel_slot` for this booking, with the booker signing along if `booker_signs`
+    fn organiser_cancel(&mut self, booker_signs: bool) -> std::result::Result<(), ProgramError> {
+        let mut metas = (contract::accounts::OrganiserCancelSlot {
+            organiser: self.organiser,
+            experience: self.experience,
+            vault: self.vault,
+            reservation: self.reservation,
+            slot: self.slot,
+            slot_schedule: schedule(&self.experience),
+            registry_page: registry_page(&self.experience),
+            prev_slot: None,
+            next_slot: None,
+            booker: self.user,
+            booker_nft_account: Some(self.user_nft_account),
+            mint: self.mint,
+            mint_authority: mint_authority(),
+            payment_mint: None,
+            vault_payment_account: None,
+            booker_payment_account: None,
+            metadata: Some(metadata(&self.mint)),
+            master_edition: Some(master_edition(&self.mint)),
+            collection_metadata: None,
+            token_program: Some(spl_token::ID),
+            ticket_token_program: None,
+            system_program: System::id(),
+            metadata_program: Some(mpl_token_metadata::ID),
+            sysvar_instructions: Some(sysvar::instructions::ID),
+            merkle_tree: None,
+            compression_program: None,
+            leaf_delegate: None,
+        }).to_account_metas(None);
+        for meta in metas.iter_mut().filter(|meta| meta.pubkey == self.user) {
+            meta.is_signer = booker_signs;
+        }
+        let data = (contract::instruction::OrganiserCancelSlot {
+            start_time: self.start_time,
+            ticket_root: None,
+            proof_len: 0,
+        }).data();
+
+        self.run(&metas, &data)
+    }
+
+    fn run(&mut self, metas: &[AccountMeta], data: &[u8]) -> std::result::Result<(), ProgramError> {
+        let infos: HashMap<Pubkey, AccountInfo> = self.accounts
+            .iter_mut()
+            .map(|(key, account)| {
+                let len = account.data().len();
+                let info = AccountInfo::new(
+                    key,
+                    false,
+                    false,
+                    &mut account.lamports,
+                    &mut account.data[8..8 + len],
+                    &account.owner,
+                    account.executable,
+                    0
+                );
+                (*key, info)
+            })
+            .collect();
+        let account_infos: Vec<AccountInfo> = metas
+            .iter()
+            .map(|meta| {
+                let mut info = infos[&meta.pubkey].clone();
+                info.is_signer = meta.is_signer;
+                info.is_writable = meta.is_writable;
+                info
+            })
+            .collect();
+
+        contract::entry(&contract::ID, &account_infos, data)
+    }
+}
+
+fn mint_authority() -> Pubkey {
+    Pubkey::find_program_address(&[b"mint_authority"], &contract::ID).0
+}
+
+fn metadata(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::Metadata::find_pda(mint).0
+}
+
+fn master_edition(mint: &Pubkey) -> Pubkey {
+    mpl_token_metadata::accounts::MasterEdition::find_pda(mint).0
+}
+
+fn schedule(experience: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"schedule", experience.as_ref()], &contract::ID).0
+}
+
+fn registry_page(experience: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"slot_registry", experience.as_ref(), 0u32.to_le_bytes().as_ref()],
+        &contract::ID
+    ).0
+}
+
+// borsh layout of a minted ticket's Metadata account, every optional field left empty
+fn ticket_metadata(mint: &Pubkey) -> Vec<u8> {
+    let mut data = vec![4]; // Key::MetadataV1
+    data.extend(mint_authority().as_ref());
+    data.extend(mint.as_ref());
+    for field in ["Harbour Walk Booking", "Harbour Wal", "https://example.com/ticket.json"] {
+        data.extend((field.len() as u32).to_le_bytes());
+        data.extend(field.as_bytes());
+    }
+    data.extend(0u16.to_le_bytes()); // seller_fee_basis_points
+    data.extend([0, 1, 1]); // no creators, primary sale happened, mutable
+    data.extend([0; 6]);
+    data
+}
+
+fn error(code: ErrorCode) -> ProgramError {
+    Error::from(code).into()
+}
+
+fn cpis() -> Vec<(Pubkey, Vec<Pubkey>)> {
+    CPIS.with(|cpis| cpis.borrow().clone())
+}
+
+#[test]
+fn cancellation_split_keeps_fee_for_organiser() {
+    assert_eq!(vault::cancellation_split(SLOT_PRICE, 10).unwrap(), (900_000_000, 100_000_000));
+    assert_eq!(vault::cancellation_split(SLOT_PRICE, 0).unwrap(), (SLOT_PRICE, 0));
+    assert_eq!(vault::cancellation_split(SLOT_PRICE, 100).unwrap(), (0, SLOT_PRICE));
+}
+
+#[test]
+fn cancellation_split_rounds_fee_down() {
+    assert_eq!(vault::cancellation_split(99, 10).unwrap(), (90, 9));
+    assert_eq!(vault::cancellation_split(u64::MAX, 50).unwrap().1, u64::MAX / 2);
+}
+
+#[test]
+fn cancellation_split_rejects_fee_above_100_percent() {
+    assert!(vault::cancellation_split(SLOT_PRICE, 101).is_err());
+}
+
+#[test]
+fn refund_follows_the_policy_tier_for_the_notice_given() {
+    let mut early = Booking::new(1, 72 * HOUR, true);
+    let user = early.user;
+    let reservation_rent = early.lamports(&early.reservation);
+    early.cancel(user, None).unwrap();
+    assert_eq!(early.lamports(&early.user), SLOT_PRICE + reservation_rent);
+    assert_eq!(early.lamports(&early.organiser), 0);
+    assert_eq!(early.lamports(&early.vault), vault_rent());
+
+    let mut late = Booking::new(1, 30 * HOUR, true);
+    let user = late.user;
+    late.cancel(user, None).unwrap();
+    assert_eq!(late.lamports(&late.user), 900_000_000 + reservation_rent);
+    assert_eq!(late.lamports(&late.organiser), 100_000_000);
+    assert_eq!(late.lamports(&late.vault), vault_rent());
+    assert!(late.is_closed(&late.reservation));
+}
+
+#[test]
+fn cancelling_inside_the_last_tier_is_too_late() {
+    let mut booking = Booking::new(1, 12 * HOUR, true);
+    let user = booking.user;
+
+    assert_eq!(booking.cancel(user, None), Err(error(ErrorCode::TooLateToCancel)));
+    assert_eq!(booking.lamports(&booking.vault), vault_rent() + SLOT_PRICE);
+    assert!(booking.reservation().is_active);
+}
+
+#[test]
+fn checked_in_guests_cannot_cancel() {
+    let mut booking = Booking::new(2, 72 * HOUR, true);
+    let user = booking.user;
+    booking.check_in();
+
+    assert_eq!(booking.cancel(user, Some(1)), Err(error(ErrorCode::AlreadyCheckedIn)));
+    assert_eq!(booking.lamports(&booking.vault), vault_rent() + 2 * SLOT_PRICE);
+}
+
+#[test]
+fn bookers_of_a_cancelled_slot_get_everything_back_whenever_they_cancel() {
+    let mut booking = Booking::new(1, -HOUR, true);
+    let user = booking.user;
+    booking.cancel_slot();
+    let reservation_rent = booking.lamports(&booking.reservation);
+
+    booking.cancel(user, None).unwrap();
+    assert_eq!(booking.lamports(&booking.user), SLOT_PRICE + reservation_rent);
+    assert_eq!(booking.lamports(&booking.organiser), 0);
+}
+
+#[test]
+fn partial_cancel_leaves_the_rest_of_the_party_in_the_vault() {
+    let mut booking = Booking::new(3, 72 * HOUR, true);
+    let user = booking.user;
+
+    booking.cancel(user, Some(1)).unwrap();
+
+    assert_eq!(booking.lamports(&booking.user), SLOT_PRICE);
+    assert_eq!(booking.lamports(&booking.vault), vault_rent() + 2 * SLOT_PRICE);
+    assert_eq!(booking.reservation().seats, 2);
+    assert_eq!(booking.slot().booked_count, 2);
+    assert!(booking.reservation().is_active);
+
+    // the ticket is rewritten for the two seats left rather than burnt
+    assert_eq!(cpis(), vec![(mpl_token_metadata::ID, vec![mint_authority()])]);
+}
+
+#[test]
+fn only_the_ticket_holder_can_cancel() {
+    let mut booking = Booking::new(1, 72 * HOUR, true);
+    let stranger = Pubkey::new_unique();
+    assert!(booking.cancel(stranger, None).is_err());
+    assert_eq!(booking.lamports(&booking.vault), vault_rent() + SLOT_PRICE);
+
+    // a buyer of the ticket is refunded instead of the original booker
+    booking.give_ticket_to(stranger);
+    let reservation_rent = booking.lamports(&booking.reservation);
+    booking.cancel(stranger, None).unwrap();
+    assert_eq!(booking.lamports(&stranger), SLOT_PRICE + reservation_rent);
+    assert!(booking.is_closed(&booking.reservation));
+}
+
+#[test]
+fn soulbound_ticket_is_thawed_by_the_program_authority_before_burning() {
+    let mut booking = Booking::new(1, 72 * HOUR, false);
+    let user = booking.user;
+
+    booking.cancel(user, None).unwrap();
+
+    assert_eq!(
+        cpis(),
+        vec![(mpl_token_metadata::ID, vec![mint_authority()]), (mpl_token_metadata::ID, Vec::new())]
+    );
+}
+
+#[test]
+fn organiser_cancel_voids_a_ticket_the_booker_does_not_sign_for() {
+    let mut booking = Booking::new(1, 12 * HOUR, true);
+    booking.book_another_seat();
+
+    booking.organiser_cancel(false).unwrap();
+
+    assert_eq!(booking.lamports(&booking.user), SLOT_PRICE);
+    assert!(booking.reservation().cancelled_by_organiser);
+    assert_eq!(cpis(), vec![(mpl_token_metadata::ID, vec![mint_authority()])]);
+}
+
+#[test]
+fn organiser_cancel_burns_the_ticket_when_the_booker_signs() {
+    let mut booking = Booking::new(1, 12 * HOUR, false);
+    booking.book_another_seat();
+
+    booking.organiser_cancel(true).unwrap();
+
+    assert_eq!(booking.lamports(&booking.user), SLOT_PRICE);
+    assert_eq!(
+        cpis(),
+        vec![(mpl_token_metadata::ID, vec![mint_authority()]), (mpl_token_metadata::ID, Vec::new())]
+    );
+}
+
+#[test]
+fn organiser_cancel_refunds_the_current_holder_only() {
+    let mut booking = Booking::new(1, 12 * HOUR, true);
+    booking.book_another_seat();
+    booking.give_ticket_to(Pubkey::new_unique());
+
+    assert_eq!(booking.organiser_cancel(false), Err(error(ErrorCode::Unauthorized)));
+    assert_eq!(booking.lamports(&booking.vault), vault_rent() + SLOT_PRICE);
+}
@@ -0,0 +1,63 @@
+use contract::cancellation_policy::{ CancellationPolicy, CancellationTier };
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+fn tier(min_notice_seconds: i64, refund_percent: u8) -> CancellationTier {
+    CancellationTier { min_notice_seconds, refund_percent }
+}
+
+fn tiered_policy() -> CancellationPolicy {
+    CancellationPolicy {
+        tiers: vec![tier(7 * DAY, 100), tier(48 * HOUR, 50), tier(0, 0)],
+    }
+}
+
+#[test]
+fn refund_follows_matching_tier() {
+    let policy = tiered_policy();
+
+    assert_eq!(policy.refund_percent(10 * DAY), Some(100));
+    assert_eq!(policy.refund_percent(7 * DAY), Some(100));
+    assert_eq!(policy.refund_percent(7 * DAY - 1), Some(50));
+    assert_eq!(policy.refund_percent(48 * HOUR), Some(50));
+    assert_eq!(policy.refund_percent(HOUR), Some(0));
+}
+
+#[test]
+fn no_tier_matches_after_slot_start() {
+    assert_eq!(tiered_policy().refund_percent(-1), None);
+    assert_eq!(CancellationPolicy::default().refund_percent(DAY - 1), None);
+}
+
+#[test]
+fn default_policy_matches_previous_behaviour() {
+    let policy = CancellationPolicy::default();
+
+    assert!(policy.validate().is_ok());
+    assert_eq!(policy.refund_percent(DAY), Some(90));
+}
+
+#[test]
+fn validate_accepts_tiered_policy() {
+    assert!(tiered_policy().validate().is_ok());
+}
+
+#[test]
+fn validate_rejects_malformed_policies() {
+    let empty = CancellationPolicy { tiers: vec![] };
+    let too_many = CancellationPolicy {
+        tiers: vec![tier(4 * DAY, 100), tier(3 * DAY, 75), tier(2 * DAY, 50), tier(DAY, 25), tier(0, 0)],
+    };
+    let over_100 = CancellationPolicy { tiers: vec![tier(DAY, 101)] };
+    let negative_notice = CancellationPolicy { tiers: vec![tier(-DAY, 50)] };
+    let unordered = CancellationPolicy { tiers: vec![tier(DAY, 50), tier(7 * DAY, 100)] };
+    let shrinking_refund = CancellationPolicy { tiers: vec![tier(7 * DAY, 50), tier(DAY, 100)] };
+
+    assert!(empty.validate().is_err());
+    assert!(too_many.validate().is_err());
+    assert!(over_100.validate().is_err());
+    assert!(negative_notice.validate().is_err());
+    assert!(unordered.validate().is_err());
+    assert!(shrinking_refund.validate().is_err());
+}
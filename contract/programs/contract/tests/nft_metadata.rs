@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use contract::{
+    cancellation_policy::CancellationPolicy,
+    nft_metadata,
+    ticket_royalties::TicketRoyalties,
+    Experience,
+    TicketMode,
+};
+
+const START: i64 = 1_700_000_000;
+const END: i64 = 1_700_003_600;
+
+fn experience(metadata_uri_template: &str) -> Experience {
+    Experience {
+        organiser: Pubkey::new_unique(),
+        title: "Harbour Walk".to_string(),
+        title_seed: "Harbour Walk".to_string(),
+        description: String::new(),
+        location: Some("Old Town & Docks".to_string()),
+        price_lamports: 1_000_000,
+        cancellation_policy: CancellationPolicy::default(),
+        metadata_uri_template: metadata_uri_template.to_string(),
+        time_slot_count: 0,
+        next_slot_seq: 0,
+        check_in_delegate: None,
+        payment_mint: None,
+        ticket_mode: TicketMode::Metaplex,
+        merkle_tree: None,
+        collection_mint: None,
+        transferable: true,
+        max_resale_markup_percent: 0,
+        resale_royalty_percent: 0,
+        ticket_royalties: TicketRoyalties::default(),
+    }
+}
+
+#[test]
+fn empty_template_falls_back_to_default_uri() {
+    let uri = nft_metadata::create_metadata_uri(
+        &experience(""),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        START,
+        END,
+        2
+    ).unwrap();
+
+    assert!(uri.starts_with("https://"));
+}
+
+#[test]
+fn template_placeholders_are_rendered_per_booking() {
+    let reservation = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let uri = nft_metadata::create_metadata_uri(
+        &experience("https://t.io/{reservation}/{mint}?s={start_time}&e={end_time}&n={seats}&l={location}"),
+        &reservation,
+        &mint,
+        START,
+        END,
+        2
+    ).unwrap();
+
+    assert_eq!(
+        uri,
+        format!("https://t.io/{reservation}/{mint}?s={START}&e={END}&n=2&l=Old+Town+%26+Docks")
+    );
+}
+
+#[test]
+fn rendered_uri_over_metaplex_limit_is_rejected() {
+    let template = format!("https://t.io/{}/{{reservation}}/{{mint}}", "a".repeat(150));
+    assert!(Experience::validate_metadata_uri_template(&template).is_ok());
+
+    let result = nft_metadata::create_metadata_uri(
+        &experience(&template),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        START,
+        END,
+        2
+    );
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn template_over_metaplex_limit_is_rejected() {
+    let template = format!("https://t.io/{}", "a".repeat(Experience::MAX_URI_LEN));
+
+    assert!(Experience::validate_metadata_uri_template(&template).is_err());
+}
+
+#[test]
+fn names_fit_metaplex_limit_at_the_longest_title() {
+    let experience = Experience {
+        title: "a".repeat(Experience::MAX_TITLE_LEN),
+        ..experience("")
+    };
+
+    for name in [
+        nft_metadata::ticket_name(&experience),
+        nft_metadata::collection_name(&experience),
+        nft_metadata::void_ticket_name(&experience),
+    ] {
+        assert!(name.len() <= mpl_token_metadata::MAX_NAME_LENGTH, "{name}");
+    }
+}
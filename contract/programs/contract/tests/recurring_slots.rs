@@ -0,0 +1,35 @@
+use contract::recurring_slots::{ slot_times, SlotInterval };
+
+const START: i64 = 1_700_000_000;
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+
+#[test]
+fn weekly_series_starts_a_week_apart() {
+    let times = slot_times(START, 2 * HOUR, SlotInterval::Weekly, 3).unwrap();
+
+    assert_eq!(
+        times,
+        vec![
+            (START, START + 2 * HOUR),
+            (START + 7 * DAY, START + 7 * DAY + 2 * HOUR),
+            (START + 14 * DAY, START + 14 * DAY + 2 * HOUR)
+        ]
+    );
+}
+
+#[test]
+fn custom_interval_is_used_as_is() {
+    let times = slot_times(START, HOUR, SlotInterval::Custom { seconds: 3 * HOUR }, 2).unwrap();
+
+    assert_eq!(times, vec![(START, START + HOUR), (START + 3 * HOUR, START + 4 * HOUR)]);
+    assert_eq!(slot_times(START, HOUR, SlotInterval::Daily, 2).unwrap()[1].0, START + DAY);
+}
+
+#[test]
+fn invalid_series_is_rejected() {
+    assert!(slot_times(START, HOUR, SlotInterval::Daily, 0).is_err());
+    assert!(slot_times(START, 0, SlotInterval::Daily, 1).is_err());
+    assert!(slot_times(START, 2 * HOUR, SlotInterval::Custom { seconds: HOUR }, 2).is_err());
+    assert!(slot_times(i64::MAX - HOUR, HOUR, SlotInterval::Daily, 2).is_err());
+}
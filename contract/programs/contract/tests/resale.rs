@@ -0,0 +1,26 @@
+use contract::resale::{ max_resale_price, resale_split };
+
+#[test]
+fn resale_cap_is_markup_over_slot_price() {
+    assert_eq!(max_resale_price(1_000, 0).unwrap(), 1_000);
+    assert_eq!(max_resale_price(1_000, 25).unwrap(), 1_250);
+    assert_eq!(max_resale_price(1_000, 300).unwrap(), 4_000);
+}
+
+#[test]
+fn resale_cap_overflow_is_rejected() {
+    assert!(max_resale_price(u64::MAX, 1).is_err());
+}
+
+#[test]
+fn royalty_is_taken_from_the_resale_price() {
+    assert_eq!(resale_split(1_000, 0).unwrap(), (1_000, 0));
+    assert_eq!(resale_split(1_000, 10).unwrap(), (900, 100));
+    assert_eq!(resale_split(999, 10).unwrap(), (900, 99));
+    assert_eq!(resale_split(1_000, 100).unwrap(), (0, 1_000));
+}
+
+#[test]
+fn royalty_over_hundred_percent_is_rejected() {
+    assert!(resale_split(1_000, 101).is_err());
+}
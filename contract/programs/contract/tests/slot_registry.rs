@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use contract::{ RegisteredSlot, SlotRegistryPage };
+
+#[test]
+fn slots_are_paged_by_sequence_number() {
+    assert_eq!(SlotRegistryPage::page_of(0), 0);
+    assert_eq!(SlotRegistryPage::page_of(SlotRegistryPage::PAGE_SIZE - 1), 0);
+    assert_eq!(SlotRegistryPage::page_of(SlotRegistryPage::PAGE_SIZE), 1);
+    assert_eq!(SlotRegistryPage::index_of(SlotRegistryPage::PAGE_SIZE + 3), 3);
+}
+
+#[test]
+fn removed_slots_stay_listed() {
+    let entry = RegisteredSlot { start_time: 1_700_000_000, end_time: 1_700_003_600, removed: false };
+    let mut page = SlotRegistryPage {
+        experience: Pubkey::new_unique(),
+        page: 1,
+        slots: vec![entry; 2],
+    };
+
+    page.mark_removed(SlotRegistryPage::PAGE_SIZE + 1).unwrap();
+    assert_eq!(page.slots.len(), 2);
+    assert!(!page.slots[0].removed);
+    assert!(page.slots[1].removed);
+    assert!(page.mark_removed(SlotRegistryPage::PAGE_SIZE + 2).is_err());
+}
@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use contract::{ SlotSchedule, TimeSlotAccount };
+
+const NOW: i64 = 1_700_000_000;
+const HOUR: i64 = 60 * 60;
+
+fn schedule(buffer_seconds: i64) -> SlotSchedule {
+    SlotSchedule {
+        experience: Pubkey::new_unique(),
+        buffer_seconds,
+        first_seq: None,
+    }
+}
+
+fn slot(seq: u64, start_time: i64, end_time: i64) -> TimeSlotAccount {
+    TimeSlotAccount {
+        experience: Pubkey::default(),
+        seq,
+        start_time,
+        end_time,
+        price: 1_000_000,
+        capacity: 1,
+        booked_count: 0,
+        is_cancelled: false,
+        held_seats: 0,
+        waitlist_head: 0,
+        waitlist_tail: 0,
+        prev_seq: None,
+        next_seq: None,
+    }
+}
+
+// schedule holding two slots, 1-2h and 5-6h from now
+fn two_slots(buffer_seconds: i64) -> (SlotSchedule, TimeSlotAccount, TimeSlotAccount) {
+    let mut schedule = schedule(buffer_seconds);
+    let mut first = slot(0, NOW + HOUR, NOW + 2 * HOUR);
+    let mut second = slot(1, NOW + 5 * HOUR, NOW + 6 * HOUR);
+    schedule.insert(&mut [&mut first], &mut []).unwrap();
+    schedule.insert(&mut [&mut second], &mut [&mut first]).unwrap();
+    (schedule, first, second)
+}
+
+#[test]
+fn new_slots_are_linked_between_their_neighbours() {
+    let (mut schedule, mut first, mut second) = two_slots(0);
+    assert_eq!(schedule.first_seq, Some(0));
+    assert_eq!((first.next_seq, second.prev_seq), (Some(1), Some(0)));
+
+    let mut middle = slot(2, NOW + 3 * HOUR, NOW + 4 * HOUR);
+    schedule.insert(&mut [&mut middle], &mut [&mut first, &mut second]).unwrap();
+    assert_eq!((first.next_seq, middle.prev_seq), (Some(2), Some(0)));
+    assert_eq!((middle.next_seq, second.prev_seq), (Some(1), Some(2)));
+
+    let mut earliest = slot(3, NOW, NOW + HOUR);
+    schedule.insert(&mut [&mut earliest], &mut [&mut first]).unwrap();
+    assert_eq!(schedule.first_seq, Some(3));
+    assert_eq!((earliest.next_seq, first.prev_seq), (Some(0), Some(3)));
+}
+
+#[test]
+fn overlapping_slots_are_rejected() {
+    let (mut schedule, mut first, mut second) = two_slots(0);
+
+    let mut overlapping = slot(2, NOW + 90 * 60, NOW + 3 * HOUR);
+    assert!(schedule.insert(&mut [&mut overlapping], &mut [&mut first, &mut second]).is_err());
+
+    // back to back sessions are fine without a buffer
+    let mut back_to_back = slot(2, NOW + 2 * HOUR, NOW + 5 * HOUR);
+    assert!(schedule.insert(&mut [&mut back_to_back], &mut [&mut first, &mut second]).is_ok());
+}
+
+#[test]
+fn buffer_keeps_sessions_apart() {
+    let (mut schedule, mut first, mut second) = two_slots(30 * 60);
+
+    let mut too_close = slot(2, NOW + 2 * HOUR, NOW + 3 * HOUR);
+    assert!(schedule.insert(&mut [&mut too_close], &mut [&mut first, &mut second]).is_err());
+
+    let mut spaced = slot(2, NOW + 2 * HOUR + 30 * 60, NOW + 4 * HOUR + 30 * 60);
+    assert!(schedule.insert(&mut [&mut spaced], &mut [&mut first, &mut second]).is_ok());
+}
+
+#[test]
+fn neighbours_must_surround_the_new_slots() {
+    let (mut schedule, mut first, mut second) = two_slots(0);
+    let mut middle = slot(2, NOW + 3 * HOUR, NOW + 4 * HOUR);
+
+    // leaving out a neighbour would skip the overlap check against it
+    assert!(schedule.insert(&mut [&mut middle], &mut [&mut first]).is_err());
+    assert!(schedule.insert(&mut [&mut middle], &mut [&mut second]).is_err());
+    assert!(schedule.insert(&mut [&mut middle], &mut []).is_err());
+    assert!(schedule.insert(&mut [&mut middle], &mut [&mut second, &mut first]).is_err());
+}
+
+#[test]
+fn removed_slots_free_their_time() {
+    let (mut schedule, first, mut second) = two_slots(0);
+    schedule.remove(&first, None, Some(&mut second)).unwrap();
+    assert_eq!(schedule.first_seq, Some(1));
+    assert_eq!(second.prev_seq, None);
+
+    let mut replacement = slot(2, NOW + HOUR, NOW + 2 * HOUR);
+    assert!(schedule.insert(&mut [&mut replacement], &mut [&mut second]).is_ok());
+
+    // the neighbours passed in must be the slot's own
+    assert!(schedule.remove(&second, None, None).is_err());
+    assert!(schedule.remove(&first, None, None).is_err());
+}
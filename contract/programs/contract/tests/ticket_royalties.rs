@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use contract::ticket_royalties::{ CoCreator, TicketRoyalties };
+
+fn co_creator(share: u8) -> CoCreator {
+    CoCreator { address: Pubkey::new_unique(), share }
+}
+
+fn royalties(co_creators: Vec<CoCreator>) -> TicketRoyalties {
+    TicketRoyalties { seller_fee_basis_points: 500, co_creators }
+}
+
+#[test]
+fn organiser_takes_the_share_left_by_co_hosts() {
+    let organiser = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let co_host = co_creator(30);
+
+    let creators = royalties(vec![co_host]).creators(organiser, authority);
+
+    assert_eq!(creators.len(), 3);
+    assert_eq!((creators[0].address, creators[0].verified, creators[0].share), (authority, true, 0));
+    assert_eq!((creators[1].address, creators[1].verified, creators[1].share), (organiser, false, 70));
+    assert_eq!((creators[2].address, creators[2].verified, creators[2].share), (co_host.address, false, 30));
+}
+
+#[test]
+fn valid_royalties_are_accepted() {
+    let organiser = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+
+    assert!(TicketRoyalties::default().validate(&organiser, &authority).is_ok());
+    assert!(royalties(vec![co_creator(50), co_creator(25), co_creator(25)]).validate(&organiser, &authority).is_ok());
+}
+
+#[test]
+fn invalid_royalties_are_rejected() {
+    let organiser = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let duplicate = co_creator(10);
+
+    let too_high = TicketRoyalties { seller_fee_basis_points: 10_001, co_creators: vec![] };
+    assert!(too_high.validate(&organiser, &authority).is_err());
+    assert!(royalties(vec![co_creator(60), co_creator(41)]).validate(&organiser, &authority).is_err());
+    assert!(royalties(vec![co_creator(1); 4]).validate(&organiser, &authority).is_err());
+    assert!(royalties(vec![duplicate, duplicate]).validate(&organiser, &authority).is_err());
+    assert!(royalties(vec![CoCreator { address: organiser, share: 10 }]).validate(&organiser, &authority).is_err());
+    assert!(royalties(vec![CoCreator { address: authority, share: 10 }]).validate(&organiser, &authority).is_err());
+}
@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use contract::{ Experience, TimeSlotAccount, WaitlistEntry };
+
+const NOW: i64 = 1_699_000_000;
+
+fn slot(capacity: u16) -> TimeSlotAccount {
+    TimeSlotAccount {
+        experience: Pubkey::new_unique(),
+        seq: 0,
+        start_time: 1_700_000_000,
+        end_time: 1_700_003_600,
+        price: 1_000_000,
+        capacity,
+        booked_count: 0,
+        is_cancelled: false,
+        held_seats: 0,
+        waitlist_head: 0,
+        waitlist_tail: 0,
+        prev_seq: None,
+        next_seq: None,
+    }
+}
+
+fn entry(position: u32, seats: u16, deposit: u64) -> WaitlistEntry {
+    WaitlistEntry {
+        slot: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        position,
+        seats,
+        deposit,
+        offer_expires_at: None,
+        withdrawn: false,
+    }
+}
+
+#[test]
+fn seats_can_be_booked_up_to_capacity() {
+    let mut slot = slot(2);
+
+    assert!(slot.book_seats(1).is_ok());
+    assert!(slot.book_seats(1).is_ok());
+    assert!(slot.book_seats(1).is_err());
+    assert_eq!(slot.booked_count, 2);
+
+    slot.release_seats(1).unwrap();
+    assert!(slot.book_seats(1).is_ok());
+}
+
+#[test]
+fn cancelled_slot_takes_no_bookings() {
+    let mut slot = slot(20);
+    slot.book_seats(1).unwrap();
+    slot.is_cancelled = true;
+
+    assert!(slot.book_seats(1).is_err());
+    assert!(slot.release_seats(1).is_ok());
+    assert!(slot.release_seats(1).is_err());
+}
+
+#[test]
+fn group_booking_must_fit_remaining_seats() {
+    let mut slot = slot(20);
+
+    assert!(slot.book_seats(15).is_ok());
+    assert!(slot.book_seats(6).is_err());
+    assert_eq!(slot.booked_count, 15);
+    assert!(slot.book_seats(5).is_ok());
+    assert!(slot.book_seats(u16::MAX).is_err());
+
+    slot.release_seats(4).unwrap();
+    assert_eq!(slot.booked_count, 16);
+}
+
+#[test]
+fn freed_seats_are_held_while_anyone_is_waiting() {
+    let mut slot = slot(2);
+    slot.book_seats(2).unwrap();
+    slot.waitlist_tail = 1;
+
+    slot.free_seats(1).unwrap();
+    assert_eq!((slot.booked_count, slot.held_seats), (1, 1));
+    assert!(slot.book_seats(1).is_err());
+
+    slot.pop_waitlist();
+    assert_eq!(slot.held_seats, 0);
+    assert!(slot.book_seats(1).is_ok());
+}
+
+#[test]
+fn only_the_head_of_the_queue_gets_an_offer() {
+    let mut slot = slot(4);
+    slot.book_seats(4).unwrap();
+    slot.waitlist_tail = 2;
+    let mut head = entry(0, 2, 0);
+    let mut next = entry(1, 1, 0);
+
+    slot.free_seats(1).unwrap();
+    assert!(!slot.offer_to(&mut head, NOW));
+    assert!(!slot.offer_to(&mut next, NOW));
+
+    slot.free_seats(1).unwrap();
+    assert!(slot.offer_to(&mut head, NOW));
+    assert_eq!(head.offer_expires_at, Some(NOW + Experience::WAITLIST_OFFER_SECONDS));
+    assert!(head.has_live_offer(NOW + Experience::WAITLIST_OFFER_SECONDS));
+    assert!(!head.has_live_offer(NOW + Experience::WAITLIST_OFFER_SECONDS + 1));
+    assert!(!slot.offer_to(&mut head, NOW));
+}
+
+#[test]
+fn prepaid_entries_are_seated_without_an_offer() {
+    let mut slot = slot(2);
+    slot.book_seats(2).unwrap();
+    slot.waitlist_tail = 1;
+    let mut head = entry(0, 2, 2_000_000);
+
+    slot.free_seats(1).unwrap();
+    assert!(!slot.can_seat(&head));
+
+    slot.free_seats(1).unwrap();
+    assert!(slot.can_seat(&head));
+    assert!(!slot.offer_to(&mut head, NOW));
+    assert_eq!(head.offer_expires_at, None);
+
+    head.withdrawn = true;
+    assert!(!slot.can_seat(&head));
+}